@@ -0,0 +1,336 @@
+use std::fmt;
+use std::str::FromStr;
+
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_crypto_primitives::Error;
+use ark_marlin::{IndexProverKey, IndexVerifierKey, Marlin, UniversalSRS};
+use ark_poly::univariate::DensePolynomial;
+use ark_poly_commit::marlin_pc::MarlinKZG10;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem, OptimizationGoal, SynthesisMode};
+use ark_serialize::CanonicalSerialize;
+use ark_std::rand::{CryptoRng, RngCore};
+use blake2::Blake2s;
+
+type MarlinPC = MarlinKZG10<Bls12_381, DensePolynomial<Fr>>;
+pub type MarlinInst = Marlin<Fr, MarlinPC, Blake2s>;
+
+// the universal SRS is generated once and indexed for every circuit up to its size
+pub type MarlinSrs = UniversalSRS<Fr, MarlinPC>;
+
+// the size of the SRS the binary generates and saves, room for cube, factor and hash (the largest,
+// about 1100 constraints and 1400 non-zero entries per matrix); see `check_index_size`
+pub const MARLIN_MAX_CONSTRAINTS: usize = 1 << 12;
+pub const MARLIN_MAX_VARIABLES: usize = 1 << 12;
+pub const MARLIN_MAX_NON_ZERO: usize = 1 << 13;
+
+// Groth16 and GM17 need a setup per circuit, Marlin a single universal SRS.
+// GM17 proofs are simulation-extractable: unlike Groth16 they cannot be re-randomized into a new valid proof
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Backend {
+    Groth16,
+    Gm17,
+    Marlin,
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "groth16" => Ok(Backend::Groth16),
+            "gm17" => Ok(Backend::Gm17),
+            "marlin" => Ok(Backend::Marlin),
+            _ => Err(format!("unknown backend {:?}, expected one of: groth16, gm17, marlin", s)),
+        }
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Backend::Groth16 => write!(f, "groth16"),
+            Backend::Gm17 => write!(f, "gm17"),
+            Backend::Marlin => write!(f, "marlin"),
+        }
+    }
+}
+
+// one key is set up or loaded per run and only moved into place, so the variants are not boxed
+#[allow(clippy::large_enum_variant)]
+pub enum ProvingKey {
+    Groth16(ark_groth16::ProvingKey<Bls12_381>),
+    Gm17(ark_gm17::ProvingKey<Bls12_381>),
+    Marlin(IndexProverKey<Fr, MarlinPC>),
+}
+
+pub enum VerifyingKey {
+    Groth16(ark_groth16::VerifyingKey<Bls12_381>),
+    Gm17(ark_gm17::VerifyingKey<Bls12_381>),
+    Marlin(IndexVerifierKey<Fr, MarlinPC>),
+}
+
+pub enum Proof {
+    Groth16(ark_groth16::Proof<Bls12_381>),
+    Gm17(ark_gm17::Proof<Bls12_381>),
+    Marlin(ark_marlin::Proof<Fr, MarlinPC>),
+}
+
+impl ProvingKey {
+    pub fn backend(&self) -> Backend {
+        match self {
+            ProvingKey::Groth16(_) => Backend::Groth16,
+            ProvingKey::Gm17(_) => Backend::Gm17,
+            ProvingKey::Marlin(_) => Backend::Marlin,
+        }
+    }
+}
+
+impl ProvingKey {
+    // the verifying key every proving key carries
+    pub fn verifying_key(&self) -> VerifyingKey {
+        match self {
+            ProvingKey::Groth16(pk) => VerifyingKey::Groth16(pk.vk.clone()),
+            ProvingKey::Gm17(pk) => VerifyingKey::Gm17(pk.vk.clone()),
+            ProvingKey::Marlin(pk) => VerifyingKey::Marlin(pk.index_vk.clone()),
+        }
+    }
+}
+
+impl VerifyingKey {
+    pub fn backend(&self) -> Backend {
+        match self {
+            VerifyingKey::Groth16(_) => Backend::Groth16,
+            VerifyingKey::Gm17(_) => Backend::Gm17,
+            VerifyingKey::Marlin(_) => Backend::Marlin,
+        }
+    }
+}
+
+// (constraints, variables, non-zero entries) of a circuit, as the Marlin indexer counts them
+pub fn index_size<C: ConstraintSynthesizer<Fr>>(circuit: C) -> Result<(usize, usize, usize), Error> {
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    cs.set_optimization_goal(OptimizationGoal::Weight);
+    cs.set_mode(SynthesisMode::Setup);
+    circuit.generate_constraints(cs.clone())?;
+    cs.finalize();
+
+    let matrices = cs.to_matrices().ok_or("constraint system has no matrices")?;
+    let num_non_zero = [&matrices.a, &matrices.b, &matrices.c]
+        .iter()
+        .map(|matrix| matrix.iter().map(|row| row.len()).sum::<usize>())
+        .max()
+        .unwrap_or(0);
+    let num_variables = cs.num_instance_variables() + cs.num_witness_variables();
+    Ok((cs.num_constraints(), num_variables, num_non_zero))
+}
+
+// Marlin only indexes circuits that fit in the SRS of the configured maximum size
+pub fn check_index_size<C: ConstraintSynthesizer<Fr>>(circuit: C) -> Result<(), Error> {
+    let (constraints, variables, non_zero) = index_size(circuit)?;
+    if constraints > MARLIN_MAX_CONSTRAINTS || variables > MARLIN_MAX_VARIABLES || non_zero > MARLIN_MAX_NON_ZERO {
+        return Err(format!(
+            "the circuit has {} constraints, {} variables and {} non-zero entries, the universal SRS is for at most {}, {} and {}",
+            constraints, variables, non_zero, MARLIN_MAX_CONSTRAINTS, MARLIN_MAX_VARIABLES, MARLIN_MAX_NON_ZERO
+        )
+        .into());
+    }
+    Ok(())
+}
+
+pub fn universal_setup<R: RngCore + CryptoRng>(
+    max_constraints: usize,
+    max_variables: usize,
+    max_non_zero: usize,
+    rng: &mut R,
+) -> Result<MarlinSrs, Error> {
+    MarlinInst::universal_setup(max_constraints, max_variables, max_non_zero, rng)
+        .map_err(|e| format!("marlin universal setup: {:?}", e).into())
+}
+
+// a Groth16 or GM17 circuit-specific setup, or a Marlin indexing of the circuit against `srs`
+pub fn setup<C: ConstraintSynthesizer<Fr>, R: RngCore + CryptoRng>(
+    backend: Backend,
+    circuit: C,
+    srs: Option<&MarlinSrs>,
+    rng: &mut R,
+) -> Result<(ProvingKey, VerifyingKey), Error> {
+    match backend {
+        Backend::Groth16 => {
+            let param = ark_groth16::generate_random_parameters::<Bls12_381, _, _>(circuit, rng)?;
+            let vk = param.vk.clone();
+            Ok((ProvingKey::Groth16(param), VerifyingKey::Groth16(vk)))
+        }
+        Backend::Gm17 => {
+            let param = ark_gm17::generate_random_parameters::<Bls12_381, _, _>(circuit, rng)?;
+            let vk = param.vk.clone();
+            Ok((ProvingKey::Gm17(param), VerifyingKey::Gm17(vk)))
+        }
+        Backend::Marlin => {
+            let srs = srs.ok_or("the marlin backend needs a universal SRS")?;
+            let (pk, vk) = MarlinInst::index(srs, circuit)
+                .map_err(|e| format!("marlin index: {:?}", e))?;
+            Ok((ProvingKey::Marlin(pk), VerifyingKey::Marlin(vk)))
+        }
+    }
+}
+
+// with delta == gamma, as in a key straight from `phase1::Phase1::proving_key`, anyone can move
+// a proof to another public input by adding IC(x) - IC(x') to C
+fn check_groth16_key(vk: &ark_groth16::VerifyingKey<Bls12_381>) -> Result<(), Error> {
+    if vk.delta_g2 == vk.gamma_g2 {
+        return Err("Groth16 key has delta equal to gamma, contribute to it in a phase-2 ceremony first".into());
+    }
+    Ok(())
+}
+
+pub fn prove<C: ConstraintSynthesizer<Fr>, R: RngCore + CryptoRng>(
+    pk: &ProvingKey,
+    circuit: C,
+    rng: &mut R,
+) -> Result<Proof, Error> {
+    match pk {
+        ProvingKey::Groth16(pk) => {
+            check_groth16_key(&pk.vk)?;
+            Ok(Proof::Groth16(ark_groth16::create_random_proof(circuit, pk, rng)?))
+        }
+        ProvingKey::Gm17(pk) => Ok(Proof::Gm17(ark_gm17::create_random_proof(circuit, pk, rng)?)),
+        ProvingKey::Marlin(pk) => MarlinInst::prove(pk, circuit, rng)
+            .map(Proof::Marlin)
+            .map_err(|e| format!("marlin prove: {:?}", e).into()),
+    }
+}
+
+pub fn verify<R: RngCore>(vk: &VerifyingKey, public_input: &[Fr], proof: &Proof, rng: &mut R) -> Result<bool, Error> {
+    match (vk, proof) {
+        (VerifyingKey::Groth16(vk), Proof::Groth16(proof)) => {
+            check_groth16_key(vk)?;
+            let pvk = ark_groth16::prepare_verifying_key(vk);
+            Ok(ark_groth16::verify_proof(&pvk, proof, public_input)?)
+        }
+        (VerifyingKey::Gm17(vk), Proof::Gm17(proof)) => {
+            let pvk = ark_gm17::prepare_verifying_key(vk);
+            Ok(ark_gm17::verify_proof(&pvk, proof, public_input)?)
+        }
+        (VerifyingKey::Marlin(vk), Proof::Marlin(proof)) => MarlinInst::verify(vk, public_input, proof, rng)
+            .map_err(|e| format!("marlin verify: {:?}", e).into()),
+        _ => Err("proof and verifying key are from different backends".into()),
+    }
+}
+
+// serialized artifacts for `encode::encode_parameters`
+impl Proof {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        match self {
+            Proof::Groth16(proof) => proof.serialize(&mut bytes).unwrap(),
+            Proof::Gm17(proof) => proof.serialize(&mut bytes).unwrap(),
+            Proof::Marlin(proof) => proof.serialize(&mut bytes).unwrap(),
+        }
+        bytes
+    }
+
+    #[cfg(test)]
+    pub fn from_bytes(backend: Backend, mut bytes: &[u8]) -> Result<Self, Error> {
+        Ok(match backend {
+            Backend::Groth16 => Proof::Groth16(ark_serialize::CanonicalDeserialize::deserialize(&mut bytes)?),
+            Backend::Gm17 => Proof::Gm17(ark_serialize::CanonicalDeserialize::deserialize(&mut bytes)?),
+            Backend::Marlin => Proof::Marlin(ark_serialize::CanonicalDeserialize::deserialize(&mut bytes)?),
+        })
+    }
+}
+
+impl VerifyingKey {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        match self {
+            VerifyingKey::Groth16(vk) => vk.serialize(&mut bytes).unwrap(),
+            VerifyingKey::Gm17(vk) => vk.serialize(&mut bytes).unwrap(),
+            VerifyingKey::Marlin(vk) => vk.serialize(&mut bytes).unwrap(),
+        }
+        bytes
+    }
+
+    #[cfg(test)]
+    pub fn from_bytes(backend: Backend, mut bytes: &[u8]) -> Result<Self, Error> {
+        Ok(match backend {
+            Backend::Groth16 => VerifyingKey::Groth16(ark_serialize::CanonicalDeserialize::deserialize(&mut bytes)?),
+            Backend::Gm17 => VerifyingKey::Gm17(ark_serialize::CanonicalDeserialize::deserialize(&mut bytes)?),
+            Backend::Marlin => VerifyingKey::Marlin(ark_serialize::CanonicalDeserialize::deserialize(&mut bytes)?),
+        })
+    }
+}
+
+#[test]
+fn test_marlin_universal_setup_for_two_circuits() {
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use crate::{to_fq, CubeDemo, FactorDemo};
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let cube = CubeDemo { x: to_fq(3) };
+    let factor = FactorDemo { p: 3, q: 11 };
+
+    // one SRS large enough for both circuits
+    let (c1, v1, n1) = index_size(cube.clone()).unwrap();
+    let (c2, v2, n2) = index_size(factor.clone()).unwrap();
+    let srs = universal_setup(c1.max(c2), v1.max(v2), n1.max(n2), &mut rng).unwrap();
+
+    let (pk, vk) = setup(Backend::Marlin, cube.clone(), Some(&srs), &mut rng).unwrap();
+    let proof = prove(&pk, cube, &mut rng).unwrap();
+    assert!(verify(&vk, &[to_fq(30)], &proof, &mut rng).unwrap());
+    assert!(!verify(&vk, &[to_fq(31)], &proof, &mut rng).unwrap());
+
+    let (pk, vk) = setup(Backend::Marlin, factor.clone(), Some(&srs), &mut rng).unwrap();
+    let proof = prove(&pk, factor, &mut rng).unwrap();
+    assert!(verify(&vk, &[to_fq(33)], &proof, &mut rng).unwrap());
+    assert!(!proof.to_bytes().is_empty());
+}
+
+#[test]
+fn test_groth16_backend() {
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use crate::{to_fq, CubeDemo};
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let cube = CubeDemo { x: to_fq(3) };
+    let (pk, vk) = setup(Backend::Groth16, cube.clone(), None, &mut rng).unwrap();
+    let proof = prove(&pk, cube.clone(), &mut rng).unwrap();
+    assert!(verify(&vk, &[to_fq(30)], &proof, &mut rng).unwrap());
+
+    // marlin cannot index without a universal SRS
+    assert!(setup(Backend::Marlin, cube, None, &mut rng).is_err());
+    assert_eq!("marlin".parse::<Backend>(), Ok(Backend::Marlin));
+    assert!("plonk".parse::<Backend>().is_err());
+}
+
+#[test]
+fn test_gm17_serialization_round_trip() {
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use crate::{to_fq, CubeDemo, FactorDemo};
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let cube = CubeDemo { x: to_fq(3) };
+    let (pk, vk) = setup(Backend::Gm17, cube.clone(), None, &mut rng).unwrap();
+    let proof = prove(&pk, cube, &mut rng).unwrap();
+    assert!(verify(&vk, &[to_fq(30)], &proof, &mut rng).unwrap());
+
+    // what `encode::encode_parameters` receives deserializes back into a verifying proof
+    let vk_bytes = vk.to_bytes();
+    let proof_bytes = proof.to_bytes();
+    let vk = VerifyingKey::from_bytes(Backend::Gm17, &vk_bytes).unwrap();
+    let proof = Proof::from_bytes(Backend::Gm17, &proof_bytes).unwrap();
+    assert_eq!(vk.to_bytes(), vk_bytes);
+    assert_eq!(proof.to_bytes(), proof_bytes);
+    assert!(verify(&vk, &[to_fq(30)], &proof, &mut rng).unwrap());
+    assert!(!verify(&vk, &[to_fq(31)], &proof, &mut rng).unwrap());
+
+    // truncated artifacts are rejected
+    assert!(Proof::from_bytes(Backend::Gm17, &proof_bytes[..proof_bytes.len() - 1]).is_err());
+
+    // a GM17 proof does not check against a Groth16 key
+    let factor = FactorDemo { p: 3, q: 11 };
+    let (_, groth16_vk) = setup(Backend::Groth16, factor, None, &mut rng).unwrap();
+    assert!(verify(&groth16_vk, &[to_fq(33)], &proof, &mut rng).is_err());
+}
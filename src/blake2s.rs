@@ -0,0 +1,161 @@
+use ark_bls12_381::Fr;
+use ark_crypto_primitives::prf::blake2s::constraints::{evaluate_blake2s, Blake2sGadget};
+use ark_crypto_primitives::prf::{Blake2s as Blake2sPrf, PRFGadget, PRF};
+use ark_ff::ToConstraintField;
+use ark_r1cs_std::prelude::*;
+use ark_r1cs_std::uint8::UInt8;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+
+pub const DIGEST_LEN: usize = 32;
+
+// native reference digest (Blake2s-256, no key, salt or personalization)
+pub fn blake2s(input: &[u8]) -> [u8; DIGEST_LEN] {
+    use blake2::{Blake2s, Digest};
+
+    let mut digest = [0u8; DIGEST_LEN];
+    digest.copy_from_slice(&Blake2s::digest(input));
+    digest
+}
+
+// native reference PRF: out = Blake2s(k || x)
+pub fn prf(key: &[u8; 32], x: &[u8; 32]) -> [u8; DIGEST_LEN] {
+    Blake2sPrf::evaluate(key, x).unwrap()
+}
+
+pub fn blake2s_gadget(input: &[UInt8<Fr>]) -> Result<Vec<UInt8<Fr>>, SynthesisError> {
+    let bits = input.to_bits_le()?;
+    let mut digest = vec![];
+    for word in evaluate_blake2s(&bits)? {
+        digest.extend(word.to_bytes()?);
+    }
+    Ok(digest)
+}
+
+// proving that I know an input whose Blake2s digest is the public digest
+#[derive(Clone)]
+pub struct Blake2sDemo {
+    pub input: Vec<u8>,
+    pub digest: [u8; DIGEST_LEN],
+}
+
+impl ConstraintSynthesizer<Fr> for Blake2sDemo {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+
+        let digest = UInt8::new_input_vec(ark_relations::ns!(cs, "digest"), &self.digest)?;
+
+        let mut input_bytes = vec![];
+        for byte in self.input.iter() {
+            input_bytes.push(UInt8::new_witness(ark_relations::ns!(cs, "preimage"), || Ok(byte))?);
+        }
+
+        let result = blake2s_gadget(&input_bytes)?;
+        result.enforce_equal(&digest)?;
+
+        Ok(())
+    }
+}
+
+// proving that out = PRF_k(x) for a secret key k and public x, out
+#[derive(Clone)]
+pub struct PrfDemo {
+    pub key: [u8; 32],
+    pub x: [u8; 32],
+    pub out: [u8; DIGEST_LEN],
+}
+
+impl ConstraintSynthesizer<Fr> for PrfDemo {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+
+        let x = UInt8::new_input_vec(ark_relations::ns!(cs, "x"), &self.x)?;
+        let out = UInt8::new_input_vec(ark_relations::ns!(cs, "out"), &self.out)?;
+
+        let key = <Blake2sGadget as PRFGadget<Blake2sPrf, Fr>>::new_seed(
+            ark_relations::ns!(cs, "key"), &self.key
+        );
+
+        let result = <Blake2sGadget as PRFGadget<Blake2sPrf, Fr>>::evaluate(&key, &x)?;
+        result.to_bytes()?.enforce_equal(&out)?;
+
+        Ok(())
+    }
+}
+
+// verifier statements: byte strings are packed into field elements
+pub fn digest_public_input(digest: &[u8; DIGEST_LEN]) -> Vec<Fr> {
+    digest.to_field_elements().unwrap()
+}
+
+pub fn prf_public_input(x: &[u8; 32], out: &[u8; DIGEST_LEN]) -> Vec<Fr> {
+    let mut statement: Vec<Fr> = x.to_field_elements().unwrap();
+    let out: Vec<Fr> = out.to_field_elements().unwrap();
+    statement.extend(out);
+    statement
+}
+
+#[test]
+fn test_blake2s_proof() {
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use ark_bls12_381::Bls12_381;
+    use ark_groth16::*;
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let input = vec![30u8];
+    let digest = blake2s(&input);
+    let circuit = Blake2sDemo { input, digest };
+
+    let param = generate_random_parameters::<Bls12_381, _, _>(circuit.clone(), &mut rng).unwrap();
+    let proof = create_random_proof(circuit, &param, &mut rng).unwrap();
+    let pvk = prepare_verifying_key(&param.vk);
+
+    let result = verify_proof(&pvk, &proof, &digest_public_input(&digest)).unwrap();
+    assert!(result);
+}
+
+#[test]
+fn test_prf_proof() {
+    use ark_std::rand::{rngs::StdRng, SeedableRng, Rng};
+    use ark_bls12_381::Bls12_381;
+    use ark_groth16::*;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let key: [u8; 32] = rng.gen();
+    let mut x = [0u8; 32];
+    x[0] = 30;
+    let out = prf(&key, &x);
+    let circuit = PrfDemo { key, x, out };
+
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    circuit.clone().generate_constraints(cs.clone()).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+
+    let param = generate_random_parameters::<Bls12_381, _, _>(circuit.clone(), &mut rng).unwrap();
+    let proof = create_random_proof(circuit, &param, &mut rng).unwrap();
+    let pvk = prepare_verifying_key(&param.vk);
+
+    let result = verify_proof(&pvk, &proof, &prf_public_input(&x, &out)).unwrap();
+    assert!(result);
+
+    // a different key gives a different output
+    let other_key: [u8; 32] = rng.gen();
+    let circuit = PrfDemo { key: other_key, x, out };
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    circuit.generate_constraints(cs.clone()).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn test_blake2s_gadget_matches_native() {
+    use ark_relations::r1cs::ConstraintSystem;
+
+    for input in [vec![30u8], vec![1u8, 2, 3], vec![7u8; 64], vec![255u8; 100]] {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let input_var = UInt8::new_witness_vec(cs.clone(), &input).unwrap();
+        let digest = blake2s_gadget(&input_var).unwrap().value().unwrap();
+
+        assert_eq!(digest, blake2s(&input).to_vec());
+        assert!(cs.is_satisfied().unwrap());
+    }
+}
@@ -0,0 +1,396 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use ark_bls12_381::{Bls12_381, Fr, G1Affine, G1Projective, G2Affine, G2Projective};
+use ark_crypto_primitives::Error;
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{Field, PrimeField, UniformRand, Zero};
+use ark_groth16::ProvingKey;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem, SynthesisMode};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::rand::{CryptoRng, RngCore, SeedableRng};
+use blake2::{Blake2s, Digest};
+use rand_chacha::ChaChaRng;
+
+use crate::backend;
+use crate::fingerprint::BoundProvingKey;
+use crate::keys::{self, Compression};
+
+// Groth16 phase-2 MPC: participants in turn multiply delta by a secret d and divide the
+// h and l queries by d, so the final delta is known to nobody as long as one participant
+// discards their d. Each contribution carries a proof of knowledge of d:
+// s, d * s in G1 and d * r in G2, with r in G2 derived from the transcript so far.
+//
+// Only delta is covered, so a ceremony starts from a phase-1 powers of tau (`init --ptau`) whose
+// tau, alpha and beta nobody knows; `generate_random_parameters` would hand them to whoever ran it.
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Contribution {
+    pub delta_after: G1Affine,
+    pub s: G1Affine,
+    pub s_delta: G1Affine,
+    pub r_delta: G2Affine,
+}
+
+impl Contribution {
+    fn write<W: Write>(&self, mut writer: W) -> Result<(), Error> {
+        self.delta_after.serialize_uncompressed(&mut writer)?;
+        self.s.serialize_uncompressed(&mut writer)?;
+        self.s_delta.serialize_uncompressed(&mut writer)?;
+        self.r_delta.serialize_uncompressed(&mut writer)?;
+        Ok(())
+    }
+
+    fn read<R: Read>(mut reader: R) -> Result<Self, Error> {
+        Ok(Contribution {
+            delta_after: CanonicalDeserialize::deserialize_uncompressed(&mut reader)?,
+            s: CanonicalDeserialize::deserialize_uncompressed(&mut reader)?,
+            s_delta: CanonicalDeserialize::deserialize_uncompressed(&mut reader)?,
+            r_delta: CanonicalDeserialize::deserialize_uncompressed(&mut reader)?,
+        })
+    }
+}
+
+// the ceremony state: the circuit, the parameters it started from, the current parameters and every
+// contribution so far
+#[derive(Clone)]
+pub struct Phase2 {
+    pub circuit_id: String,
+    pub initial: ProvingKey<Bls12_381>,
+    pub params: ProvingKey<Bls12_381>,
+    pub contributions: Vec<Contribution>,
+}
+
+// the start of the transcript
+fn hash_params(circuit_id: &str, params: &ProvingKey<Bls12_381>) -> Result<Vec<u8>, Error> {
+    let mut bytes = Vec::new();
+    circuit_id.as_bytes().to_vec().serialize(&mut bytes)?;
+    params.serialize_uncompressed(&mut bytes)?;
+    Ok(Blake2s::digest(&bytes).to_vec())
+}
+
+// the transcript hash after each contribution
+fn next_transcript(transcript: &[u8], contribution: &Contribution) -> Result<Vec<u8>, Error> {
+    let mut bytes = transcript.to_vec();
+    contribution.write(&mut bytes)?;
+    Ok(Blake2s::digest(&bytes).to_vec())
+}
+
+// the G2 point a contribution proves knowledge of d against, nobody knows its discrete log
+fn transcript_to_g2(transcript: &[u8], s: &G1Affine, s_delta: &G1Affine) -> Result<G2Affine, Error> {
+    let mut bytes = transcript.to_vec();
+    s.serialize_uncompressed(&mut bytes)?;
+    s_delta.serialize_uncompressed(&mut bytes)?;
+    let mut seed = [0u8; 32];
+    seed.copy_from_slice(&Blake2s::digest(&bytes));
+    Ok(G2Projective::rand(&mut ChaChaRng::from_seed(seed)).into_affine())
+}
+
+// e(a, b) == e(c, d)
+pub(crate) fn same_ratio(a: G1Affine, b: G2Affine, c: G1Affine, d: G2Affine) -> bool {
+    Bls12_381::pairing(a, b) == Bls12_381::pairing(c, d)
+}
+
+// a random linear combination of the points, to check a ratio on all of them with one pairing
+pub(crate) fn combine(points: &[G1Affine], coefficients: &[Fr]) -> G1Affine {
+    points.iter()
+        .zip(coefficients)
+        .map(|(p, c)| p.mul(c.into_repr()))
+        .sum::<G1Projective>()
+        .into_affine()
+}
+
+fn scale_all(points: &[G1Affine], scalar: Fr) -> Vec<G1Affine> {
+    let mut projective: Vec<G1Projective> = points.iter().map(|p| p.mul(scalar.into_repr())).collect();
+    G1Projective::batch_normalization(&mut projective);
+    projective.into_iter().map(|p| p.into_affine()).collect()
+}
+
+impl Phase2 {
+    // starts from parameters without contributions, see `phase1::Phase1::proving_key`
+    pub fn from_params(circuit_id: &str, params: ProvingKey<Bls12_381>) -> Self {
+        Phase2 { circuit_id: circuit_id.to_string(), initial: params.clone(), params, contributions: vec![] }
+    }
+
+    fn transcript(&self) -> Result<Vec<u8>, Error> {
+        let mut transcript = hash_params(&self.circuit_id, &self.initial)?;
+        for contribution in self.contributions.iter() {
+            transcript = next_transcript(&transcript, contribution)?;
+        }
+        Ok(transcript)
+    }
+
+    // multiplies delta by a fresh secret, which is dropped on return
+    pub fn contribute<R: RngCore + CryptoRng>(&mut self, rng: &mut R) -> Result<(), Error> {
+        let transcript = self.transcript()?;
+
+        let d = Fr::rand(rng);
+        let d_inv = d.inverse().ok_or("zero contribution")?;
+
+        let s = G1Projective::rand(rng).into_affine();
+        let s_delta = s.mul(d.into_repr()).into_affine();
+        let r = transcript_to_g2(&transcript, &s, &s_delta)?;
+        let r_delta = r.mul(d.into_repr()).into_affine();
+
+        let params = &mut self.params;
+        params.delta_g1 = params.delta_g1.mul(d.into_repr()).into_affine();
+        params.vk.delta_g2 = params.vk.delta_g2.mul(d.into_repr()).into_affine();
+        params.h_query = scale_all(&params.h_query, d_inv);
+        params.l_query = scale_all(&params.l_query, d_inv);
+
+        self.contributions.push(Contribution { delta_after: params.delta_g1, s, s_delta, r_delta });
+        Ok(())
+    }
+
+    // checks every contribution and that the current parameters follow from the initial ones
+    pub fn verify<R: RngCore>(&self, rng: &mut R) -> Result<(), Error> {
+        let (initial, params) = (&self.initial, &self.params);
+
+        // the initial delta is the generator, known to everyone
+        if self.contributions.is_empty() {
+            return Err("no contributions, the initial parameters are not safe to use".into());
+        }
+
+        let mut transcript = hash_params(&self.circuit_id, initial)?;
+        let mut delta = initial.delta_g1;
+        for (i, contribution) in self.contributions.iter().enumerate() {
+            if contribution.s.is_zero() || contribution.s_delta.is_zero() {
+                return Err(format!("contribution {}: degenerate proof of knowledge", i).into());
+            }
+            let r = transcript_to_g2(&transcript, &contribution.s, &contribution.s_delta)?;
+            if !same_ratio(contribution.s, contribution.r_delta, contribution.s_delta, r) {
+                return Err(format!("contribution {}: invalid proof of knowledge", i).into());
+            }
+            if !same_ratio(delta, contribution.r_delta, contribution.delta_after, r) {
+                return Err(format!("contribution {}: delta does not follow from the previous one", i).into());
+            }
+            delta = contribution.delta_after;
+            transcript = next_transcript(&transcript, contribution)?;
+        }
+
+        if params.delta_g1 != delta {
+            return Err("delta does not match the last contribution".into());
+        }
+        // compared against the initial delta rather than the generators, which it need not be
+        if !same_ratio(params.delta_g1, initial.vk.delta_g2, initial.delta_g1, params.vk.delta_g2) {
+            return Err("delta in G1 and G2 differ".into());
+        }
+        if params.vk.delta_g2 == params.vk.gamma_g2 {
+            return Err("delta equals gamma, proofs could be moved to other public inputs".into());
+        }
+
+        // everything but delta and the queries divided by it is untouched
+        let unchanged = params.vk.alpha_g1 == initial.vk.alpha_g1
+            && params.vk.beta_g2 == initial.vk.beta_g2
+            && params.vk.gamma_g2 == initial.vk.gamma_g2
+            && params.vk.gamma_abc_g1 == initial.vk.gamma_abc_g1
+            && params.beta_g1 == initial.beta_g1
+            && params.a_query == initial.a_query
+            && params.b_g1_query == initial.b_g1_query
+            && params.b_g2_query == initial.b_g2_query;
+        if !unchanged {
+            return Err("parameters other than delta were modified".into());
+        }
+
+        // h_i * delta and l_i * delta are the same as initially
+        for (name, before, after) in [
+            ("h", &initial.h_query, &params.h_query),
+            ("l", &initial.l_query, &params.l_query),
+        ] {
+            if before.len() != after.len() {
+                return Err(format!("{} query has the wrong length", name).into());
+            }
+            let coefficients: Vec<Fr> = (0..before.len()).map(|_| Fr::rand(rng)).collect();
+            if !same_ratio(combine(after, &coefficients), params.vk.delta_g2, combine(before, &coefficients), initial.vk.delta_g2) {
+                return Err(format!("{} query is not divided by delta", name).into());
+            }
+        }
+
+        Ok(())
+    }
+
+    // the verified final parameters as a key pair saved for `circuit`, which `run --keys` loads
+    pub fn export<C: ConstraintSynthesizer<Fr> + Clone, R: RngCore>(
+        &self,
+        dir: impl AsRef<Path>,
+        circuit: C,
+        compression: Compression,
+        rng: &mut R,
+    ) -> Result<(), Error> {
+        self.verify(rng)?;
+
+        // parameters of another circuit would not prove anything
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        cs.set_mode(SynthesisMode::Setup);
+        circuit.clone().generate_constraints(cs.clone())?;
+        let num_variables = cs.num_instance_variables() + cs.num_witness_variables();
+        if self.params.vk.gamma_abc_g1.len() != cs.num_instance_variables() || self.params.a_query.len() != num_variables {
+            return Err(format!("the ceremony parameters are not for the {} circuit", self.circuit_id).into());
+        }
+
+        let pk = BoundProvingKey::bind(backend::ProvingKey::Groth16(self.params.clone()), circuit)?;
+        let vk = backend::VerifyingKey::Groth16(self.params.vk.clone());
+        // the ceremony rng only samples the verification, the keys come from the contributions
+        keys::save_keys(dir, &self.circuit_id, &keys::SavedKeys { pk, vk, insecure_seed: None }, compression)
+    }
+
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<(), Error> {
+        self.circuit_id.as_bytes().to_vec().serialize(&mut writer)?;
+        self.initial.serialize_uncompressed(&mut writer)?;
+        self.params.serialize_uncompressed(&mut writer)?;
+        (self.contributions.len() as u64).serialize(&mut writer)?;
+        for contribution in self.contributions.iter() {
+            contribution.write(&mut writer)?;
+        }
+        Ok(())
+    }
+
+    pub fn read<R: Read>(mut reader: R) -> Result<Self, Error> {
+        let circuit_id = String::from_utf8(Vec::<u8>::deserialize(&mut reader)?)?;
+        let initial = CanonicalDeserialize::deserialize_uncompressed(&mut reader)?;
+        let params = CanonicalDeserialize::deserialize_uncompressed(&mut reader)?;
+        let num_contributions = u64::deserialize(&mut reader)?;
+        let contributions = (0..num_contributions)
+            .map(|_| Contribution::read(&mut reader))
+            .collect::<Result<_, _>>()?;
+        Ok(Phase2 { circuit_id, initial, params, contributions })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::read(BufReader::new(File::open(path)?))
+    }
+}
+
+// usage: arkworks-example ceremony init <cube | factor> <file> --ptau <ptau, challenge or response file>
+//        arkworks-example ceremony contribute <file>
+//        arkworks-example ceremony verify <file>
+//        arkworks-example ceremony export <file> --keys <dir> [--uncompressed]
+pub fn command(args: &[String]) -> Result<(), Error> {
+    use ark_std::rand::rngs::OsRng;
+    use crate::phase1::Phase1;
+    use crate::{CubeDemo, FactorDemo};
+
+    let unknown = |circuit: &str| -> Error { format!("unknown circuit {:?}, expected one of: cube, factor", circuit).into() };
+
+    match args.iter().map(|arg| arg.as_str()).collect::<Vec<_>>().as_slice() {
+        ["init", circuit, file, "--ptau", ptau] => {
+            let phase1 = Phase1::load(ptau)?;
+            let params = match *circuit {
+                "cube" => phase1.proving_key(CubeDemo { x: Fr::zero() })?,
+                "factor" => phase1.proving_key(FactorDemo { p: 0, q: 0 })?,
+                other => return Err(unknown(other)),
+            };
+            let phase2 = Phase2::from_params(circuit, params);
+            phase2.save(file)?;
+            println!("initialized {}", file);
+        }
+        ["init", _, _] => {
+            return Err("init needs a phase-1 --ptau file: parameters from a random setup are known to whoever generates them".into());
+        }
+        ["contribute", file] => {
+            let mut phase2 = Phase2::load(file)?;
+            phase2.contribute(&mut OsRng)?;
+            phase2.save(file)?;
+            println!("contribution {} added to {}", phase2.contributions.len(), file);
+        }
+        ["verify", file] => {
+            let phase2 = Phase2::load(file)?;
+            phase2.verify(&mut OsRng)?;
+            println!("{}: {} valid contributions for {}", file, phase2.contributions.len(), phase2.circuit_id);
+        }
+        ["export", file, "--keys", dir, rest @ ..] => {
+            let compression = match rest {
+                [] => Compression::Compressed,
+                ["--uncompressed"] => Compression::Uncompressed,
+                _ => return Err(format!("unexpected arguments {:?}", rest).into()),
+            };
+            let phase2 = Phase2::load(file)?;
+            match phase2.circuit_id.as_str() {
+                "cube" => phase2.export(dir, CubeDemo { x: Fr::zero() }, compression, &mut OsRng)?,
+                "factor" => phase2.export(dir, FactorDemo { p: 0, q: 0 }, compression, &mut OsRng)?,
+                other => return Err(unknown(other)),
+            }
+            println!("saved groth16 keys for {} to {}", phase2.circuit_id, dir);
+        }
+        _ => {
+            return Err(
+                "usage: ceremony init <cube | factor> <file> --ptau <file> | contribute <file> | verify <file> | export <file> --keys <dir> [--uncompressed]".into()
+            )
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_ceremony() {
+    use ark_std::rand::rngs::StdRng;
+    use ark_groth16::*;
+    use crate::phase1::phase1_from_secrets;
+    use crate::{to_fq, CubeDemo, FactorDemo};
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let phase1 = phase1_from_secrets(8, Fr::rand(&mut rng), Fr::rand(&mut rng), Fr::rand(&mut rng));
+    let mut phase2 = Phase2::from_params("cube", phase1.proving_key(CubeDemo { x: Fr::zero() }).unwrap());
+    assert!(phase2.verify(&mut rng).is_err());
+    for _ in 0..3 {
+        phase2.contribute(&mut rng).unwrap();
+        phase2.verify(&mut rng).unwrap();
+    }
+    assert_ne!(phase2.params.delta_g1, phase2.initial.delta_g1);
+
+    // the transcript survives a round-trip through a file
+    let path = std::env::temp_dir().join("arkworks-example-ceremony-test");
+    phase2.save(&path).unwrap();
+    let loaded = Phase2::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.contributions, phase2.contributions);
+    assert_eq!(loaded.circuit_id, "cube");
+    loaded.verify(&mut rng).unwrap();
+
+    // the transcript binds the circuit
+    let mut relabeled = loaded.clone();
+    relabeled.circuit_id = "factor".to_string();
+    assert!(relabeled.verify(&mut rng).is_err());
+
+    // the final parameters still prove the circuit
+    let proof = create_random_proof(CubeDemo { x: to_fq(3) }, &phase2.params, &mut rng).unwrap();
+    let pvk = prepare_verifying_key(&phase2.params.vk);
+    assert!(verify_proof(&pvk, &proof, &[to_fq(30)]).unwrap());
+
+    // and export as keys the binary loads
+    let dir = std::env::temp_dir().join("arkworks-example-ceremony-keys-test");
+    assert!(phase2.export(&dir, FactorDemo { p: 0, q: 0 }, Compression::Compressed, &mut rng).is_err());
+    phase2.export(&dir, CubeDemo { x: Fr::zero() }, Compression::Compressed, &mut rng).unwrap();
+    let keys::SavedKeys { pk, vk, .. } = keys::load_keys(&dir, "cube", backend::Backend::Groth16).unwrap().unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    let proof = pk.prove(CubeDemo { x: to_fq(3) }, &mut rng).unwrap();
+    assert!(backend::verify(&vk, &[to_fq(30)], &proof, &mut rng).unwrap());
+    assert!(pk.prove(FactorDemo { p: 3, q: 11 }, &mut rng).is_err());
+
+    // tampering with the parameters or a contribution is detected
+    let mut tampered = phase2.clone();
+    tampered.params.h_query[0] = tampered.params.h_query[1];
+    assert!(tampered.verify(&mut rng).is_err());
+
+    let mut tampered = phase2.clone();
+    tampered.params.delta_g1 = tampered.initial.delta_g1;
+    tampered.params.vk.delta_g2 = tampered.initial.vk.delta_g2;
+    assert!(tampered.verify(&mut rng).is_err());
+
+    let mut tampered = phase2.clone();
+    tampered.contributions[1].r_delta = tampered.contributions[0].r_delta;
+    assert!(tampered.verify(&mut rng).is_err());
+
+    let mut tampered = phase2;
+    tampered.contributions.remove(1);
+    assert!(tampered.verify(&mut rng).is_err());
+}
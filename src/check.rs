@@ -0,0 +1,188 @@
+use std::fmt;
+
+use ark_bls12_381::Fr;
+use ark_crypto_primitives::Error;
+use ark_ff::{BigInteger, PrimeField, Zero};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisMode};
+
+use crate::stats::{self, Trace};
+
+// a variable of a failing constraint with its coefficient, assignment and the namespace it was allocated in
+#[derive(Clone, Debug)]
+pub struct Term {
+    pub coeff: Fr,
+    // "1" for the constant, "input i" or "witness i" otherwise
+    pub variable: String,
+    pub value: Fr,
+    pub path: String,
+}
+
+// the first constraint a * b = c that the witness does not satisfy
+#[derive(Clone, Debug)]
+pub struct Unsatisfied {
+    pub index: usize,
+    // the innermost `ns!` label of the circuit around the constraint, see `stats::is_namespace`
+    pub path: String,
+    // every r1cs span around it, gadgets included, as `ConstraintSystemRef::constraint_names` gives it
+    pub gadgets: String,
+    pub a: (Fr, Vec<Term>),
+    pub b: (Fr, Vec<Term>),
+    pub c: (Fr, Vec<Term>),
+}
+
+#[derive(Clone, Debug)]
+pub enum Failure {
+    // the circuit's public inputs differ from the statement the verifier gets, at this position
+    Statement { index: usize, assigned: Option<Fr>, expected: Option<Fr> },
+    Constraint(Unsatisfied),
+}
+
+// small values and their negations in decimal, anything else as the hex of its representation
+fn display_value(x: &Fr) -> String {
+    let small = |x: &Fr| {
+        let repr = x.into_repr();
+        match repr.as_ref().iter().skip(1).all(|limb| *limb == 0) {
+            true => Some(repr.as_ref()[0]),
+            false => None,
+        }
+    };
+    match (small(x), small(&-*x)) {
+        (Some(n), _) => n.to_string(),
+        (None, Some(n)) => format!("-{}", n),
+        (None, None) => format!("0x{}", hex::encode(x.into_repr().to_bytes_be())),
+    }
+}
+
+fn display_path(path: &str) -> &str {
+    if path.is_empty() {
+        "outside any namespace"
+    } else {
+        path
+    }
+}
+
+impl fmt::Display for Unsatisfied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "constraint {} is not satisfied: {}", self.index, display_path(&self.path))?;
+        writeln!(f, "  in gadget spans: {}", if self.gadgets.is_empty() { "none" } else { &self.gadgets })?;
+        for (name, (sum, terms)) in [("A", &self.a), ("B", &self.b), ("C", &self.c)] {
+            writeln!(f, "  {} = {}", name, display_value(sum))?;
+            for term in terms.iter() {
+                let (coeff, value) = (display_value(&term.coeff), display_value(&term.value));
+                match term.variable.as_str() {
+                    "1" => writeln!(f, "    {} * 1", coeff)?,
+                    variable => writeln!(f, "    {} * {} = {} * {}  ({})", coeff, variable, coeff, value, display_path(&term.path))?,
+                }
+            }
+        }
+        let (a, b, c) = (self.a.0, self.b.0, self.c.0);
+        writeln!(f, "  A * B = {} but C = {}", display_value(&(a * b)), display_value(&c))
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::Statement { index, assigned, expected } => {
+                let value = |x: &Option<Fr>| x.as_ref().map_or("missing".to_string(), display_value);
+                writeln!(f, "statement[{}] is {} but the circuit's public input is {}", index, value(expected), value(assigned))
+            }
+            Failure::Constraint(unsatisfied) => unsatisfied.fmt(f),
+        }
+    }
+}
+
+// synthesizes the circuit with its witness and reports the first constraint it violates, or else
+// the first public input that differs from the statement
+pub fn check<C: ConstraintSynthesizer<Fr>>(circuit: C, statement: &[Fr]) -> Result<Option<Failure>, Error> {
+    let (cs, trace) = stats::synthesize(circuit, SynthesisMode::Prove { construct_matrices: true })?;
+    if let Some(unsatisfied) = first_unsatisfied(&cs, &trace)? {
+        return Ok(Some(Failure::Constraint(unsatisfied)));
+    }
+
+    let system = cs.borrow().ok_or("constraint system was dropped")?;
+    // without the constant one
+    let assigned = &system.instance_assignment[1..];
+    Ok((0..assigned.len().max(statement.len()))
+        .find(|&index| assigned.get(index) != statement.get(index))
+        .map(|index| Failure::Statement { index, assigned: assigned.get(index).copied(), expected: statement.get(index).copied() }))
+}
+
+// `which_is_unsatisfied` only gives the span path of the failing constraint; this evaluates the
+// rows of the matrices against the assignment to find its index and what it involves
+pub fn first_unsatisfied(cs: &ConstraintSystemRef<Fr>, trace: &Trace) -> Result<Option<Unsatisfied>, Error> {
+    if cs.which_is_unsatisfied()?.is_none() {
+        return Ok(None);
+    }
+
+    cs.finalize();
+    let matrices = cs.to_matrices().ok_or("constraint system has no matrices")?;
+    let system = cs.borrow().ok_or("constraint system was dropped")?;
+    let num_instance = system.instance_assignment.len();
+    let row_terms = |row: &[(Fr, usize)]| -> (Fr, Vec<Term>) {
+        let mut sum = Fr::zero();
+        let terms = row
+            .iter()
+            .map(|(coeff, column)| {
+                let (variable, value, path) = match *column {
+                    0 => ("1".to_string(), system.instance_assignment[0], ""),
+                    i if i < num_instance => (format!("input {}", i), system.instance_assignment[i], trace.input_paths[i].as_str()),
+                    i => {
+                        let j = i - num_instance;
+                        (format!("witness {}", j), system.witness_assignment[j], trace.witness_paths[j].as_str())
+                    }
+                };
+                sum += *coeff * value;
+                Term { coeff: *coeff, variable, value, path: path.to_string() }
+            })
+            .collect();
+        (sum, terms)
+    };
+
+    for index in 0..matrices.num_constraints {
+        let (a, b, c) = (row_terms(&matrices.a[index]), row_terms(&matrices.b[index]), row_terms(&matrices.c[index]));
+        if a.0 * b.0 != c.0 {
+            let path = trace.constraint_paths[index].clone();
+            let gadgets = cs.constraint_names().and_then(|names| names.get(index).cloned()).unwrap_or_default();
+            return Ok(Some(Unsatisfied { index, path, gadgets, a, b, c }));
+        }
+    }
+    Err("which_is_unsatisfied found a constraint but every row holds".into())
+}
+
+#[test]
+fn test_check_reports_failing_constraint() {
+    use crate::{to_fq, CubeDemo, FactorDemo};
+
+    assert!(check(CubeDemo { x: to_fq(3) }, &[to_fq(30)]).unwrap().is_none());
+    assert!(check(FactorDemo { p: 3, q: 11 }, &[to_fq(33)]).unwrap().is_none());
+
+    // a satisfied circuit for another statement
+    match check(CubeDemo { x: to_fq(3) }, &[to_fq(31)]).unwrap() {
+        Some(Failure::Statement { index, assigned, expected }) => {
+            assert_eq!((index, assigned, expected), (0, Some(to_fq(30)), Some(to_fq(31))));
+        }
+        other => panic!("expected a statement mismatch, got {:?}", other),
+    }
+
+    // a wrong x^3 + x: the output no longer matches x^3 + x
+    let (cs, trace) = stats::synthesize(CubeDemo { x: to_fq(3) }, SynthesisMode::Prove { construct_matrices: true }).unwrap();
+    cs.borrow_mut().unwrap().instance_assignment[1] = to_fq(31);
+    let unsatisfied = first_unsatisfied(&cs, &trace).unwrap().unwrap();
+    println!("{}", unsatisfied);
+    assert_eq!(unsatisfied.index, 2);
+    // `enforce_equal` is called outside any label of the circuit
+    assert_eq!(unsatisfied.path, "");
+    assert!(unsatisfied.gadgets.contains("enforce_equal"));
+    let terms = unsatisfied.a.1.iter().chain(&unsatisfied.b.1).chain(&unsatisfied.c.1);
+    let out = terms.clone().find(|term| term.variable == "input 1").unwrap();
+    assert_eq!((out.value, out.path.as_str()), (to_fq(31), "new witness x^3 + x"));
+    assert!(terms.clone().any(|term| term.path == "new witness x^3"));
+    assert_ne!(unsatisfied.a.0 * unsatisfied.b.0, unsatisfied.c.0);
+
+    // 1 is not a prime factor, so the witness fails one of the range checks
+    match check(FactorDemo { p: 1, q: 33 }, &[to_fq(33)]).unwrap() {
+        Some(Failure::Constraint(unsatisfied)) => println!("{}", unsatisfied),
+        other => panic!("expected an unsatisfied constraint, got {:?}", other),
+    }
+}
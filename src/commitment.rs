@@ -0,0 +1,169 @@
+use ark_bls12_381::Fr;
+use ark_crypto_primitives::commitment::pedersen::{
+    self,
+    constraints::{CommGadget, ParametersVar, RandomnessVar},
+    Randomness,
+};
+use ark_crypto_primitives::commitment::{CommitmentGadget, CommitmentScheme};
+use ark_crypto_primitives::Error;
+use ark_ed_on_bls12_381::{
+    constraints::EdwardsVar, EdwardsAffine, EdwardsProjective as JubJub, Fr as JubJubScalar,
+};
+use ark_ff::UniformRand;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_r1cs_std::uint8::UInt8;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_std::cmp::Ordering;
+use ark_std::rand::Rng;
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct ValueWindow;
+
+// `WINDOW_SIZE * NUM_WINDOWS` = 64 bits = enough for committing to a u64 value.
+impl ark_crypto_primitives::crh::pedersen::Window for ValueWindow {
+    const WINDOW_SIZE: usize = 4;
+    const NUM_WINDOWS: usize = 16;
+}
+
+pub type ValueCommitment = pedersen::Commitment<JubJub, ValueWindow>;
+pub(crate) type ValueCommitmentGadget = CommGadget<JubJub, EdwardsVar, ValueWindow>;
+
+pub type Parameters = pedersen::Parameters<JubJub>;
+
+pub fn setup<R: Rng>(rng: &mut R) -> Result<Parameters, Error> {
+    ValueCommitment::setup(rng)
+}
+
+pub fn sample_randomness<R: Rng>(rng: &mut R) -> Randomness<JubJub> {
+    Randomness(JubJubScalar::rand(rng))
+}
+
+// Comm(v, r) = sum_i v_i * G_i + r * H over the little-endian bits of v
+pub fn commit(
+    params: &Parameters,
+    value: u64,
+    randomness: &Randomness<JubJub>,
+) -> Result<EdwardsAffine, Error> {
+    ValueCommitment::commit(params, &value.to_le_bytes(), randomness)
+}
+
+pub fn open(
+    params: &Parameters,
+    commitment: &EdwardsAffine,
+    value: u64,
+    randomness: &Randomness<JubJub>,
+) -> Result<bool, Error> {
+    Ok(commit(params, value, randomness)? == *commitment)
+}
+
+// proving that I know (value, randomness) opening the public commitment,
+// and optionally that value <= max_value
+#[derive(Clone)]
+pub struct CommitmentDemo {
+    pub params: Parameters,
+    pub commitment: EdwardsAffine,
+    pub value: u64,
+    pub randomness: Randomness<JubJub>,
+    pub max_value: Option<u64>,
+}
+
+impl ConstraintSynthesizer<Fr> for CommitmentDemo {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+
+        let commitment = <EdwardsVar as AllocVar<EdwardsAffine, _>>::new_input(
+            ark_relations::ns!(cs, "commitment"), || Ok(self.commitment)
+        )?;
+
+        let params = ParametersVar::new_constant(ark_relations::ns!(cs, "parameters"), &self.params)?;
+
+        let value_bytes = UInt8::new_witness_vec(
+            ark_relations::ns!(cs, "value"), &self.value.to_le_bytes()
+        )?;
+        let randomness = RandomnessVar::new_witness(
+            ark_relations::ns!(cs, "randomness"), || Ok(&self.randomness)
+        )?;
+
+        let result = ValueCommitmentGadget::commit(&params, &value_bytes, &randomness)?;
+        result.enforce_equal(&commitment)?;
+
+        if let Some(max_value) = self.max_value {
+            let value = Boolean::le_bits_to_fp_var(&value_bytes.to_bits_le()?)?;
+            value.enforce_cmp(&FpVar::constant(Fr::from(max_value)), Ordering::Less, true)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_commitment_proof() {
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use ark_bls12_381::Bls12_381;
+    use ark_groth16::*;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let params = setup(&mut rng).unwrap();
+    let randomness = sample_randomness(&mut rng);
+    let commitment = commit(&params, 30, &randomness).unwrap();
+    assert!(open(&params, &commitment, 30, &randomness).unwrap());
+    assert!(!open(&params, &commitment, 31, &randomness).unwrap());
+
+    let circuit = CommitmentDemo {
+        params,
+        commitment,
+        value: 30,
+        randomness,
+        max_value: Some(100),
+    };
+
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    circuit.clone().generate_constraints(cs.clone()).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+
+    let param = generate_random_parameters::<Bls12_381, _, _>(circuit.clone(), &mut rng).unwrap();
+    let proof = create_random_proof(circuit, &param, &mut rng).unwrap();
+    let pvk = prepare_verifying_key(&param.vk);
+
+    let statement = vec![commitment.x, commitment.y];
+    let result = verify_proof(&pvk, &proof, &statement).unwrap();
+    assert!(result);
+}
+
+#[test]
+fn test_commitment_bad_opening() {
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use ark_relations::r1cs::ConstraintSystem;
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let params = setup(&mut rng).unwrap();
+    let randomness = sample_randomness(&mut rng);
+    let commitment = commit(&params, 30, &randomness).unwrap();
+
+    // wrong value
+    let circuit = CommitmentDemo {
+        params: params.clone(),
+        commitment,
+        value: 31,
+        randomness: randomness.clone(),
+        max_value: None,
+    };
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    circuit.generate_constraints(cs.clone()).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+
+    // correct opening but value above the bound
+    let circuit = CommitmentDemo {
+        params,
+        commitment,
+        value: 30,
+        randomness,
+        max_value: Some(29),
+    };
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    circuit.generate_constraints(cs.clone()).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+}
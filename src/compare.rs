@@ -0,0 +1,209 @@
+use ark_bls12_381::Fr;
+use ark_ff::{BigInteger, Field, PrimeField};
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::SynthesisError;
+#[cfg(test)]
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef};
+
+// the gadgets below work on n-bit integers, with n small enough that
+// sums and differences of two of them never wrap around the field
+pub const MAX_BITS: usize = 128;
+
+fn two_to_the(n: usize) -> Fr {
+    Fr::from(2u64).pow([n as u64])
+}
+
+// decomposes x into n witness bits, which enforces 0 <= x < 2^n
+pub fn enforce_bit_length(x: &FpVar<Fr>, n: usize) -> Result<Vec<Boolean<Fr>>, SynthesisError> {
+    assert!(n <= MAX_BITS + 1);
+
+    let cs = x.cs();
+    let mode = if cs.is_none() { AllocationMode::Constant } else { AllocationMode::Witness };
+    let value = x.value().ok();
+
+    let mut bits = vec![];
+    for i in 0..n {
+        bits.push(Boolean::new_variable(
+            ark_relations::ns!(cs, "bit"),
+            || value.map(|v| v.into_repr().get_bit(i)).ok_or(SynthesisError::AssignmentMissing),
+            mode,
+        )?);
+    }
+    Boolean::le_bits_to_fp_var(&bits)?.enforce_equal(x)?;
+    Ok(bits)
+}
+
+// unsigned a < b for a, b already known to be in [0, 2^n):
+// a - b + 2^n is in [1, 2^(n+1)) and its top bit is set iff a >= b
+pub fn is_less_than(a: &FpVar<Fr>, b: &FpVar<Fr>, n: usize) -> Result<Boolean<Fr>, SynthesisError> {
+    assert!(n <= MAX_BITS);
+
+    let shifted = a - b + FpVar::constant(two_to_the(n));
+    let bits = enforce_bit_length(&shifted, n + 1)?;
+    Ok(bits[n].not())
+}
+
+pub fn is_greater_or_equal(a: &FpVar<Fr>, b: &FpVar<Fr>, n: usize) -> Result<Boolean<Fr>, SynthesisError> {
+    Ok(is_less_than(a, b, n)?.not())
+}
+
+#[cfg(test)]
+pub fn min(a: &FpVar<Fr>, b: &FpVar<Fr>, n: usize) -> Result<FpVar<Fr>, SynthesisError> {
+    let a_is_less = is_less_than(a, b, n)?;
+    FpVar::conditionally_select(&a_is_less, a, b)
+}
+
+#[cfg(test)]
+pub fn max(a: &FpVar<Fr>, b: &FpVar<Fr>, n: usize) -> Result<FpVar<Fr>, SynthesisError> {
+    let a_is_less = is_less_than(a, b, n)?;
+    FpVar::conditionally_select(&a_is_less, b, a)
+}
+
+// signed integers in [-2^(n-1), 2^(n-1)) encoded as `to_fq` does: adding 2^(n-1)
+// maps them in order onto [0, 2^n), where the unsigned gadgets apply
+#[cfg(test)]
+fn to_unsigned(x: &FpVar<Fr>, n: usize) -> FpVar<Fr> {
+    x + FpVar::constant(two_to_the(n - 1))
+}
+
+#[cfg(test)]
+pub fn enforce_signed_bit_length(x: &FpVar<Fr>, n: usize) -> Result<(), SynthesisError> {
+    enforce_bit_length(&to_unsigned(x, n), n)?;
+    Ok(())
+}
+
+#[cfg(test)]
+pub fn signed_is_less_than(a: &FpVar<Fr>, b: &FpVar<Fr>, n: usize) -> Result<Boolean<Fr>, SynthesisError> {
+    is_less_than(&to_unsigned(a, n), &to_unsigned(b, n), n)
+}
+
+#[cfg(test)]
+pub fn signed_is_greater_or_equal(a: &FpVar<Fr>, b: &FpVar<Fr>, n: usize) -> Result<Boolean<Fr>, SynthesisError> {
+    Ok(signed_is_less_than(a, b, n)?.not())
+}
+
+#[cfg(test)]
+pub fn signed_min(a: &FpVar<Fr>, b: &FpVar<Fr>, n: usize) -> Result<FpVar<Fr>, SynthesisError> {
+    let a_is_less = signed_is_less_than(a, b, n)?;
+    FpVar::conditionally_select(&a_is_less, a, b)
+}
+
+#[cfg(test)]
+pub fn signed_max(a: &FpVar<Fr>, b: &FpVar<Fr>, n: usize) -> Result<FpVar<Fr>, SynthesisError> {
+    let a_is_less = signed_is_less_than(a, b, n)?;
+    FpVar::conditionally_select(&a_is_less, b, a)
+}
+
+// proving that I know a secret age >= min_age
+#[cfg(test)]
+#[derive(Clone)]
+pub struct AgeDemo {
+    pub age: u64,
+    pub min_age: u64,
+}
+
+// ages fit in a byte
+#[cfg(test)]
+const AGE_BITS: usize = 8;
+
+#[cfg(test)]
+impl ConstraintSynthesizer<Fr> for AgeDemo {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+
+        let min_age = FpVar::<Fr>::new_input(ark_relations::ns!(cs, "min age"), || Ok(Fr::from(self.min_age)))?;
+        let age = FpVar::<Fr>::new_witness(ark_relations::ns!(cs, "age"), || Ok(Fr::from(self.age)))?;
+
+        enforce_bit_length(&min_age, AGE_BITS)?;
+        enforce_bit_length(&age, AGE_BITS)?;
+
+        is_greater_or_equal(&age, &min_age, AGE_BITS)?.enforce_equal(&Boolean::TRUE)?;
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_age_proof() {
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use ark_bls12_381::Bls12_381;
+    use ark_groth16::*;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let circuit = AgeDemo { age: 30, min_age: 18 };
+
+    let param = generate_random_parameters::<Bls12_381, _, _>(circuit.clone(), &mut rng).unwrap();
+    let proof = create_random_proof(circuit, &param, &mut rng).unwrap();
+    let pvk = prepare_verifying_key(&param.vk);
+
+    assert!(verify_proof(&pvk, &proof, &[Fr::from(18u64)]).unwrap());
+    assert!(!verify_proof(&pvk, &proof, &[Fr::from(21u64)]).unwrap());
+
+    for (age, satisfied) in [(17, false), (18, true), (19, true), (255, true), (256, false)] {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        AgeDemo { age, min_age: 18 }.generate_constraints(cs.clone()).unwrap();
+        assert_eq!(cs.is_satisfied().unwrap(), satisfied, "age {}", age);
+    }
+}
+
+#[test]
+fn test_unsigned_comparison_exhaustive() {
+    use ark_relations::r1cs::ConstraintSystem;
+    use crate::to_fq;
+
+    const N: usize = 4;
+    for a in 0..(1i64 << N) {
+        for b in 0..(1i64 << N) {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let a_var = FpVar::<Fr>::new_witness(cs.clone(), || Ok(to_fq(a))).unwrap();
+            let b_var = FpVar::<Fr>::new_witness(cs.clone(), || Ok(to_fq(b))).unwrap();
+            enforce_bit_length(&a_var, N).unwrap();
+            enforce_bit_length(&b_var, N).unwrap();
+
+            assert_eq!(is_less_than(&a_var, &b_var, N).unwrap().value().unwrap(), a < b);
+            assert_eq!(is_greater_or_equal(&a_var, &b_var, N).unwrap().value().unwrap(), a >= b);
+            assert_eq!(min(&a_var, &b_var, N).unwrap().value().unwrap(), to_fq(a.min(b)));
+            assert_eq!(max(&a_var, &b_var, N).unwrap().value().unwrap(), to_fq(a.max(b)));
+            assert!(cs.is_satisfied().unwrap());
+        }
+    }
+
+    // out of range values do not decompose
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    let x = FpVar::<Fr>::new_witness(cs.clone(), || Ok(to_fq(1 << N))).unwrap();
+    enforce_bit_length(&x, N).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn test_signed_comparison_exhaustive() {
+    use ark_relations::r1cs::ConstraintSystem;
+    use crate::to_fq;
+
+    const N: usize = 4;
+    let range = -(1i64 << (N - 1))..(1i64 << (N - 1));
+    for a in range.clone() {
+        for b in range.clone() {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let a_var = FpVar::<Fr>::new_witness(cs.clone(), || Ok(to_fq(a))).unwrap();
+            let b_var = FpVar::<Fr>::new_witness(cs.clone(), || Ok(to_fq(b))).unwrap();
+            enforce_signed_bit_length(&a_var, N).unwrap();
+            enforce_signed_bit_length(&b_var, N).unwrap();
+
+            assert_eq!(signed_is_less_than(&a_var, &b_var, N).unwrap().value().unwrap(), a < b);
+            assert_eq!(signed_is_greater_or_equal(&a_var, &b_var, N).unwrap().value().unwrap(), a >= b);
+            assert_eq!(signed_min(&a_var, &b_var, N).unwrap().value().unwrap(), to_fq(a.min(b)));
+            assert_eq!(signed_max(&a_var, &b_var, N).unwrap().value().unwrap(), to_fq(a.max(b)));
+            assert!(cs.is_satisfied().unwrap());
+        }
+    }
+
+    for x in [-(1i64 << (N - 1)) - 1, 1i64 << (N - 1)] {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let x_var = FpVar::<Fr>::new_witness(cs.clone(), || Ok(to_fq(x))).unwrap();
+        enforce_signed_bit_length(&x_var, N).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+}
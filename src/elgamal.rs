@@ -0,0 +1,167 @@
+use ark_bls12_381::Fr;
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ed_on_bls12_381::{constraints::EdwardsVar, EdwardsAffine, EdwardsProjective, Fr as JubJubScalar};
+use ark_ff::{BigInteger, PrimeField, UniformRand, Zero};
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_std::cmp::Ordering;
+use ark_std::rand::Rng;
+
+pub type SecretKey = JubJubScalar;
+pub type PublicKey = EdwardsAffine;
+
+// exponential ElGamal: the message m is encrypted as the point m * G
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ciphertext {
+    pub c1: EdwardsAffine,
+    pub c2: EdwardsAffine,
+}
+
+fn generator() -> EdwardsAffine {
+    EdwardsAffine::prime_subgroup_generator()
+}
+
+pub fn keygen<R: Rng>(rng: &mut R) -> (SecretKey, PublicKey) {
+    let sk = JubJubScalar::rand(rng);
+    (sk, generator().mul(sk.into_repr()).into_affine())
+}
+
+// (c1, c2) = (r * G, m * G + r * pk)
+pub fn encrypt(pk: &PublicKey, message: u64, randomness: &JubJubScalar) -> Ciphertext {
+    let c1 = generator().mul(randomness.into_repr());
+    let c2 = generator().mul(JubJubScalar::from(message).into_repr()) + pk.mul(randomness.into_repr());
+    Ciphertext { c1: c1.into_affine(), c2: c2.into_affine() }
+}
+
+// m * G = c2 - sk * c1, then m is found by searching up to max_message
+pub fn decrypt(sk: &SecretKey, ciphertext: &Ciphertext, max_message: u64) -> Option<u64> {
+    let point = ciphertext.c2.into_projective() - ciphertext.c1.mul(sk.into_repr());
+
+    let mut candidate = EdwardsProjective::zero();
+    for m in 0..=max_message {
+        if candidate == point {
+            return Some(m);
+        }
+        candidate.add_assign_mixed(&generator());
+    }
+    None
+}
+
+// proving that the public ciphertext encrypts a hidden message under the public key,
+// and optionally that message <= max_message
+#[derive(Clone)]
+pub struct ElGamalDemo {
+    pub pk: PublicKey,
+    pub ciphertext: Ciphertext,
+    pub message: u64,
+    pub randomness: JubJubScalar,
+    pub max_message: Option<u64>,
+}
+
+impl ConstraintSynthesizer<Fr> for ElGamalDemo {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+
+        let pk = <EdwardsVar as AllocVar<EdwardsAffine, _>>::new_input(
+            ark_relations::ns!(cs, "public key"), || Ok(self.pk)
+        )?;
+        let c1 = <EdwardsVar as AllocVar<EdwardsAffine, _>>::new_input(
+            ark_relations::ns!(cs, "c1"), || Ok(self.ciphertext.c1)
+        )?;
+        let c2 = <EdwardsVar as AllocVar<EdwardsAffine, _>>::new_input(
+            ark_relations::ns!(cs, "c2"), || Ok(self.ciphertext.c2)
+        )?;
+
+        let message_bits = Vec::<Boolean<Fr>>::new_witness(
+            ark_relations::ns!(cs, "message"), || Ok((0..64).map(|i| (self.message >> i) & 1 == 1).collect::<Vec<_>>())
+        )?;
+        let randomness_bits = Vec::<Boolean<Fr>>::new_witness(
+            ark_relations::ns!(cs, "randomness"), || Ok(self.randomness.into_repr().to_bits_le())
+        )?;
+
+        let g = <EdwardsVar as AllocVar<EdwardsAffine, _>>::new_constant(
+            ark_relations::ns!(cs, "generator"), generator()
+        )?;
+
+        g.scalar_mul_le(randomness_bits.iter())?.enforce_equal(&c1)?;
+
+        let message_point = g.scalar_mul_le(message_bits.iter())?;
+        let shared = pk.scalar_mul_le(randomness_bits.iter())?;
+        (message_point + shared).enforce_equal(&c2)?;
+
+        if let Some(max_message) = self.max_message {
+            let message = Boolean::le_bits_to_fp_var(&message_bits)?;
+            message.enforce_cmp(&FpVar::constant(Fr::from(max_message)), Ordering::Less, true)?;
+        }
+
+        Ok(())
+    }
+}
+
+// the verifier statement: public key, then the ciphertext
+pub fn public_input(pk: &PublicKey, ciphertext: &Ciphertext) -> Vec<Fr> {
+    vec![pk.x, pk.y, ciphertext.c1.x, ciphertext.c1.y, ciphertext.c2.x, ciphertext.c2.y]
+}
+
+#[test]
+fn test_elgamal_native() {
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let (sk, pk) = keygen(&mut rng);
+    for message in [0u64, 1, 30, 1000] {
+        let ciphertext = encrypt(&pk, message, &JubJubScalar::rand(&mut rng));
+        assert_eq!(decrypt(&sk, &ciphertext, 1000), Some(message));
+    }
+
+    let ciphertext = encrypt(&pk, 1001, &JubJubScalar::rand(&mut rng));
+    assert_eq!(decrypt(&sk, &ciphertext, 1000), None);
+
+    // another key does not decrypt
+    let (other_sk, _) = keygen(&mut rng);
+    let ciphertext = encrypt(&pk, 30, &JubJubScalar::rand(&mut rng));
+    assert_eq!(decrypt(&other_sk, &ciphertext, 100), None);
+}
+
+#[test]
+fn test_elgamal_proof() {
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use ark_bls12_381::Bls12_381;
+    use ark_groth16::*;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let (_, pk) = keygen(&mut rng);
+    let randomness = JubJubScalar::rand(&mut rng);
+    let ciphertext = encrypt(&pk, 30, &randomness);
+
+    let circuit = ElGamalDemo {
+        pk,
+        ciphertext: ciphertext.clone(),
+        message: 30,
+        randomness,
+        max_message: Some(100),
+    };
+
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    circuit.clone().generate_constraints(cs.clone()).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+
+    let param = generate_random_parameters::<Bls12_381, _, _>(circuit.clone(), &mut rng).unwrap();
+    let proof = create_random_proof(circuit.clone(), &param, &mut rng).unwrap();
+    let pvk = prepare_verifying_key(&param.vk);
+
+    assert!(verify_proof(&pvk, &proof, &public_input(&pk, &ciphertext)).unwrap());
+
+    // the message breaks the predicate
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    ElGamalDemo { max_message: Some(29), ..circuit.clone() }.generate_constraints(cs.clone()).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+
+    // the ciphertext does not encrypt the claimed message
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    ElGamalDemo { message: 31, ..circuit }.generate_constraints(cs.clone()).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+}
@@ -1,13 +1,16 @@
 use serde::{Deserialize, Serialize};
 
 use std::fs::File;
-use std::fs::OpenOptions;
 use std::io::BufReader;
 use std::io::Write;
 use std::io;
+use std::path::Path;
 
 use core::fmt::Write as encode_write;
 
+// the snarkjs-style JSON exports read by `encode_uncompressed_2inputs` and `encode_multi_uncompressed`,
+// which the binary does not call; they are kept for converting such exports by hand
+#[allow(dead_code)]
 #[derive(Serialize, Deserialize, Debug)]
 struct Proof {
     pi_a: Vec<u8>,
@@ -15,6 +18,7 @@ struct Proof {
     pi_c: Vec<u8>,
 }
 
+#[allow(dead_code)]
 #[derive(Serialize, Deserialize, Debug)]
 struct VerifyKey {
     alpha_1: Vec<u8>,
@@ -35,35 +39,42 @@ pub fn encode_hex(bytes: &[u8]) -> String {
     s
 }
 
-pub fn encode_parameters(proof_serialized: Vec<u8>, vkey_serialized: Vec<u8>, public_input: Vec<u8>) -> Result<(), io::Error> {
+// writes proof.txt, vkey.txt and input.txt to `dir`, the binary uses ./file;
+// `warning`, if any, is written as a `# ` line before the hex of each artifact
+pub fn encode_parameters(dir: &Path, proof_serialized: Vec<u8>, vkey_serialized: Vec<u8>, public_input: Vec<u8>, warning: Option<String>) -> Result<(), io::Error> {
 
     println!("... encoding serialized parameters");
 
     // let file = OpenOptions::new().read(true);
-    
-    let proof_hex = format!("{}{}", "0x", encode_hex(&proof_serialized));
 
-    let vkey_hex = format!("{}{}", "0x", encode_hex(&vkey_serialized));
+    let header = warning.map_or(String::new(), |warning| format!("# {}\n", warning));
+
+    let proof_hex = format!("{}{}{}", header, "0x", encode_hex(&proof_serialized));
+
+    let vkey_hex = format!("{}{}{}", header, "0x", encode_hex(&vkey_serialized));
+
+    let input_hex = format!("{}{}{}", header, "0x", encode_hex(&public_input));
 
-    let input_hex = format!("{}{}", "0x", encode_hex(&public_input));
+    std::fs::create_dir_all(dir)?;
 
-    let mut file_proof = File::create("./file/proof.txt").unwrap();
-    file_proof.write(proof_hex.as_bytes())?;
+    let mut file_proof = File::create(dir.join("proof.txt"))?;
+    file_proof.write_all(proof_hex.as_bytes())?;
 
-    let mut file_vkey = File::create("./file/vkey.txt").unwrap();
-    file_vkey.write(vkey_hex.as_bytes())?;
+    let mut file_vkey = File::create(dir.join("vkey.txt"))?;
+    file_vkey.write_all(vkey_hex.as_bytes())?;
 
-    let mut file_input = File::create("./file/input.txt").unwrap();
-    file_input.write(input_hex.as_bytes())?;
+    let mut file_input = File::create(dir.join("input.txt"))?;
+    file_input.write_all(input_hex.as_bytes())?;
 
 
     Ok(())
 }
 
+#[allow(dead_code)]
 pub fn encode_uncompressed_2inputs() -> Result<(), io::Error> {
 
-    let proof_file = File::open("./file/proof_uncompressed.json").unwrap();
-    let vkey_file = File::open("./file/vkey_uncompressed.json").unwrap();
+    let proof_file = File::open("./file/proof_uncompressed.json")?;
+    let vkey_file = File::open("./file/vkey_uncompressed.json")?;
 
     println!("... encoding uncompressed files");
 
@@ -72,8 +83,8 @@ pub fn encode_uncompressed_2inputs() -> Result<(), io::Error> {
     let proof_reader = BufReader::new(proof_file);
     let vkey_reader = BufReader::new(vkey_file);
 
-    let deserialized_proof: Proof = serde_json::from_reader(proof_reader).unwrap();
-    let deserialized_vkey: VerifyKey = serde_json::from_reader(vkey_reader).unwrap();
+    let deserialized_proof: Proof = serde_json::from_reader(proof_reader)?;
+    let deserialized_vkey: VerifyKey = serde_json::from_reader(vkey_reader)?;
 
     let pi_a = deserialized_proof.pi_a;
     let pi_b = deserialized_proof.pi_b;
@@ -99,49 +110,50 @@ pub fn encode_uncompressed_2inputs() -> Result<(), io::Error> {
     let res_vg = format!("{}{}", "0x", encode_hex(&vkey_g));
     let res_vd1 = format!("{}{}", "0x", encode_hex(&vkey_d1));
     let res_vd2 = format!("{}{}", "0x", encode_hex(&vkey_d2));
-    let res_vic1 = format!("{}{}", "0x", encode_hex(&vkey_ic_1));
-    let res_vic2 = format!("{}{}", "0x", encode_hex(&vkey_ic_2));
-    let res_vic3 = format!("{}{}", "0x", encode_hex(&vkey_ic_3));
-
-    let mut file_proofa = File::create("./file/proof_a.txt").unwrap();
-    file_proofa.write(res_a.as_bytes())?;
-    let mut file_proofb = File::create("./file/proof_b.txt").unwrap();
-    file_proofb.write(res_b.as_bytes())?;
-    let mut file_proofc = File::create("./file/proof_c.txt").unwrap();
-    file_proofc.write(res_c.as_bytes())?;
-
-    let mut file_vkey_a = File::create("./file/vkey_a.txt").unwrap();
-    file_vkey_a.write(res_va.as_bytes())?;
-    let mut file_vkey_b1 = File::create("./file/vkey_b1.txt").unwrap();
-    file_vkey_b1.write(res_vb1.as_bytes())?;
-    let mut file_vkey_b2 = File::create("./file/vkey_b2.txt").unwrap();
-    file_vkey_b2.write(res_vb2.as_bytes())?;
-    let mut file_vkey_g = File::create("./file/vkey_g.txt").unwrap();
-    file_vkey_g.write(res_vg.as_bytes())?;
-    let mut file_vkey_d1 = File::create("./file/vkey_d1.txt").unwrap();
-    file_vkey_d1.write(res_vd1.as_bytes())?;
-    let mut file_vkey_d2 = File::create("./file/vkey_d2.txt").unwrap();
-    file_vkey_d2.write(res_vd2.as_bytes())?;
-    let mut file_vkey_ic_1 = File::create("./file/vkey_ic_1.txt").unwrap();
-    file_vkey_ic_1.write(res_vic1.as_bytes())?;
-    let mut file_vkey_ic_2 = File::create("./file/vkey_ic_2.txt").unwrap();
-    file_vkey_ic_2.write(res_vic2.as_bytes())?;
-    let mut file_vkey_ic_3 = File::create("./file/vkey_ic_3.txt").unwrap();
-    file_vkey_ic_3.write(res_vic3.as_bytes())?;
+    let res_vic1 = format!("{}{}", "0x", encode_hex(vkey_ic_1));
+    let res_vic2 = format!("{}{}", "0x", encode_hex(vkey_ic_2));
+    let res_vic3 = format!("{}{}", "0x", encode_hex(vkey_ic_3));
+
+    let mut file_proofa = File::create("./file/proof_a.txt")?;
+    file_proofa.write_all(res_a.as_bytes())?;
+    let mut file_proofb = File::create("./file/proof_b.txt")?;
+    file_proofb.write_all(res_b.as_bytes())?;
+    let mut file_proofc = File::create("./file/proof_c.txt")?;
+    file_proofc.write_all(res_c.as_bytes())?;
+
+    let mut file_vkey_a = File::create("./file/vkey_a.txt")?;
+    file_vkey_a.write_all(res_va.as_bytes())?;
+    let mut file_vkey_b1 = File::create("./file/vkey_b1.txt")?;
+    file_vkey_b1.write_all(res_vb1.as_bytes())?;
+    let mut file_vkey_b2 = File::create("./file/vkey_b2.txt")?;
+    file_vkey_b2.write_all(res_vb2.as_bytes())?;
+    let mut file_vkey_g = File::create("./file/vkey_g.txt")?;
+    file_vkey_g.write_all(res_vg.as_bytes())?;
+    let mut file_vkey_d1 = File::create("./file/vkey_d1.txt")?;
+    file_vkey_d1.write_all(res_vd1.as_bytes())?;
+    let mut file_vkey_d2 = File::create("./file/vkey_d2.txt")?;
+    file_vkey_d2.write_all(res_vd2.as_bytes())?;
+    let mut file_vkey_ic_1 = File::create("./file/vkey_ic_1.txt")?;
+    file_vkey_ic_1.write_all(res_vic1.as_bytes())?;
+    let mut file_vkey_ic_2 = File::create("./file/vkey_ic_2.txt")?;
+    file_vkey_ic_2.write_all(res_vic2.as_bytes())?;
+    let mut file_vkey_ic_3 = File::create("./file/vkey_ic_3.txt")?;
+    file_vkey_ic_3.write_all(res_vic3.as_bytes())?;
 
     Ok(())
 }
 
 
+#[allow(dead_code)]
 pub fn encode_multi_uncompressed(count: u32, max_count: u32) -> Result<(), io::Error> {
 
-    let proof_file = File::open(format!("{}{}{}", "./batch_file/proof", count, "_uncompressed.json")).unwrap();
+    let proof_file = File::open(format!("{}{}{}", "./batch_file/proof", count, "_uncompressed.json"))?;
 
     // let file = OpenOptions::new().read(true);
 
     let proof_reader = BufReader::new(proof_file);
 
-    let deserialized_proof: Proof = serde_json::from_reader(proof_reader).unwrap();
+    let deserialized_proof: Proof = serde_json::from_reader(proof_reader)?;
 
     let pi_a = deserialized_proof.pi_a;
     let pi_b = deserialized_proof.pi_b;
@@ -151,17 +163,17 @@ pub fn encode_multi_uncompressed(count: u32, max_count: u32) -> Result<(), io::E
     let res_b = format!("{}{}", "0x", encode_hex(&pi_b));
     let res_c = format!("{}{}", "0x", encode_hex(&pi_c));
 
-    let mut file_proofa = File::create(format!("{}{}{}", "./batch_file/proof", count, "_a.txt")).unwrap();
-    file_proofa.write(res_a.as_bytes())?;
-    let mut file_proofb = File::create(format!("{}{}{}", "./batch_file/proof", count, "_b.txt")).unwrap();
-    file_proofb.write(res_b.as_bytes())?;
-    let mut file_proofc = File::create(format!("{}{}{}", "./batch_file/proof", count, "_c.txt")).unwrap();
-    file_proofc.write(res_c.as_bytes())?;
+    let mut file_proofa = File::create(format!("{}{}{}", "./batch_file/proof", count, "_a.txt"))?;
+    file_proofa.write_all(res_a.as_bytes())?;
+    let mut file_proofb = File::create(format!("{}{}{}", "./batch_file/proof", count, "_b.txt"))?;
+    file_proofb.write_all(res_b.as_bytes())?;
+    let mut file_proofc = File::create(format!("{}{}{}", "./batch_file/proof", count, "_c.txt"))?;
+    file_proofc.write_all(res_c.as_bytes())?;
 
     if count == max_count {
-        let vkey_file = File::open(format!("{}{}{}", "./batch_file/vkey", count, "_uncompressed.json")).unwrap();
+        let vkey_file = File::open(format!("{}{}{}", "./batch_file/vkey", count, "_uncompressed.json"))?;
         let vkey_reader = BufReader::new(vkey_file);
-        let deserialized_vkey: VerifyKey = serde_json::from_reader(vkey_reader).unwrap();
+        let deserialized_vkey: VerifyKey = serde_json::from_reader(vkey_reader)?;
 
         let vkey_a = deserialized_vkey.alpha_1;
         let vkey_b1 = deserialized_vkey.beta_1;
@@ -178,25 +190,25 @@ pub fn encode_multi_uncompressed(count: u32, max_count: u32) -> Result<(), io::E
         let res_vg = format!("{}{}", "0x", encode_hex(&vkey_g));
         let res_vd1 = format!("{}{}", "0x", encode_hex(&vkey_d1));
         let res_vd2 = format!("{}{}", "0x", encode_hex(&vkey_d2));
-        let res_vic1 = format!("{}{}", "0x", encode_hex(&vkey_ic_1));
-        let res_vic2 = format!("{}{}", "0x", encode_hex(&vkey_ic_2));
-
-        let mut file_vkey_a = File::create(format!("{}{}{}", "./batch_file/vkey", count, "_a.txt")).unwrap();
-        file_vkey_a.write(res_va.as_bytes())?;
-        let mut file_vkey_b1 = File::create(format!("{}{}{}", "./batch_file/vkey", count, "_b1.txt")).unwrap();
-        file_vkey_b1.write(res_vb1.as_bytes())?;
-        let mut file_vkey_b2 = File::create(format!("{}{}{}", "./batch_file/vkey", count, "_b2.txt")).unwrap();
-        file_vkey_b2.write(res_vb2.as_bytes())?;
-        let mut file_vkey_g = File::create(format!("{}{}{}", "./batch_file/vkey", count, "_g.txt")).unwrap();
-        file_vkey_g.write(res_vg.as_bytes())?;
-        let mut file_vkey_d1 = File::create(format!("{}{}{}", "./batch_file/vkey", count, "_d1.txt")).unwrap();
-        file_vkey_d1.write(res_vd1.as_bytes())?;
-        let mut file_vkey_d2 = File::create(format!("{}{}{}", "./batch_file/vkey", count, "_d2.txt")).unwrap();
-        file_vkey_d2.write(res_vd2.as_bytes())?;
-        let mut file_vkey_ic_1 = File::create(format!("{}{}{}", "./batch_file/vkey", count, "_ic_1.txt")).unwrap();
-        file_vkey_ic_1.write(res_vic1.as_bytes())?;
-        let mut file_vkey_ic_2 = File::create(format!("{}{}{}", "./batch_file/vkey", count, "_ic_2.txt")).unwrap();
-        file_vkey_ic_2.write(res_vic2.as_bytes())?;
+        let res_vic1 = format!("{}{}", "0x", encode_hex(vkey_ic_1));
+        let res_vic2 = format!("{}{}", "0x", encode_hex(vkey_ic_2));
+
+        let mut file_vkey_a = File::create(format!("{}{}{}", "./batch_file/vkey", count, "_a.txt"))?;
+        file_vkey_a.write_all(res_va.as_bytes())?;
+        let mut file_vkey_b1 = File::create(format!("{}{}{}", "./batch_file/vkey", count, "_b1.txt"))?;
+        file_vkey_b1.write_all(res_vb1.as_bytes())?;
+        let mut file_vkey_b2 = File::create(format!("{}{}{}", "./batch_file/vkey", count, "_b2.txt"))?;
+        file_vkey_b2.write_all(res_vb2.as_bytes())?;
+        let mut file_vkey_g = File::create(format!("{}{}{}", "./batch_file/vkey", count, "_g.txt"))?;
+        file_vkey_g.write_all(res_vg.as_bytes())?;
+        let mut file_vkey_d1 = File::create(format!("{}{}{}", "./batch_file/vkey", count, "_d1.txt"))?;
+        file_vkey_d1.write_all(res_vd1.as_bytes())?;
+        let mut file_vkey_d2 = File::create(format!("{}{}{}", "./batch_file/vkey", count, "_d2.txt"))?;
+        file_vkey_d2.write_all(res_vd2.as_bytes())?;
+        let mut file_vkey_ic_1 = File::create(format!("{}{}{}", "./batch_file/vkey", count, "_ic_1.txt"))?;
+        file_vkey_ic_1.write_all(res_vic1.as_bytes())?;
+        let mut file_vkey_ic_2 = File::create(format!("{}{}{}", "./batch_file/vkey", count, "_ic_2.txt"))?;
+        file_vkey_ic_2.write_all(res_vic2.as_bytes())?;
     }
 
     Ok(())
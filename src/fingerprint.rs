@@ -0,0 +1,83 @@
+use ark_bls12_381::Fr;
+use ark_crypto_primitives::Error;
+use ark_ff::ToBytes;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem, OptimizationGoal, SynthesisMode};
+use ark_std::rand::{CryptoRng, RngCore};
+use blake2::{Blake2s, Digest};
+
+use crate::backend::{self, Proof, ProvingKey};
+
+// blake2s of the R1CS matrices of a circuit synthesized in setup mode: the variable counts,
+// then for each of A, B and C every row as its length and (column, coefficient) pairs.
+// It only depends on `generate_constraints`, not on the witness, so any change to the
+// constraints changes it and a key set up for the old circuit no longer proves.
+pub fn fingerprint<C: ConstraintSynthesizer<Fr>>(circuit: C) -> Result<Vec<u8>, Error> {
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    cs.set_optimization_goal(OptimizationGoal::Constraints);
+    cs.set_mode(SynthesisMode::Setup);
+    circuit.generate_constraints(cs.clone())?;
+    cs.finalize();
+    let matrices = cs.to_matrices().ok_or("constraint system has no matrices")?;
+
+    let mut bytes = vec![];
+    for count in [matrices.num_instance_variables, matrices.num_witness_variables, matrices.num_constraints] {
+        (count as u64).write(&mut bytes)?;
+    }
+    for matrix in [&matrices.a, &matrices.b, &matrices.c] {
+        for row in matrix.iter() {
+            (row.len() as u64).write(&mut bytes)?;
+            for (coeff, column) in row.iter() {
+                (*column as u64).write(&mut bytes)?;
+                coeff.write(&mut bytes)?;
+            }
+        }
+    }
+    Ok(Blake2s::digest(&bytes).to_vec())
+}
+
+// a proving key together with the fingerprint of the circuit it was set up for
+pub struct BoundProvingKey {
+    pub pk: ProvingKey,
+    pub fingerprint: Vec<u8>,
+}
+
+impl BoundProvingKey {
+    pub fn bind<C: ConstraintSynthesizer<Fr>>(pk: ProvingKey, circuit: C) -> Result<Self, Error> {
+        Ok(BoundProvingKey { pk, fingerprint: fingerprint(circuit)? })
+    }
+
+    // refuses to prove a circuit whose constraints differ from the ones the key was set up for
+    pub fn prove<C: ConstraintSynthesizer<Fr> + Clone, R: RngCore + CryptoRng>(&self, circuit: C, rng: &mut R) -> Result<Proof, Error> {
+        if fingerprint(circuit.clone())? != self.fingerprint {
+            return Err("the circuit changed since the key was set up, run setup again".into());
+        }
+        backend::prove(&self.pk, circuit, rng)
+    }
+}
+
+#[test]
+fn test_fingerprint_binds_keys_to_circuits() {
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use crate::backend::{setup, verify, Backend};
+    use crate::{to_fq, CubeDemo, FactorDemo};
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    // deterministic and independent of the witness
+    let cube = fingerprint(CubeDemo { x: to_fq(3) }).unwrap();
+    assert_eq!(cube, fingerprint(CubeDemo { x: to_fq(4) }).unwrap());
+    // but differs between circuits, and between differently shaped instances of one circuit
+    assert_ne!(cube, fingerprint(FactorDemo { p: 3, q: 11 }).unwrap());
+    assert_ne!(
+        fingerprint(crate::matrix::MatrixDemo::blank(2, 3, 2)).unwrap(),
+        fingerprint(crate::matrix::MatrixDemo::blank(2, 2, 3)).unwrap()
+    );
+
+    let (pk, vk) = setup(Backend::Groth16, CubeDemo { x: to_fq(3) }, None, &mut rng).unwrap();
+    let pk = BoundProvingKey::bind(pk, CubeDemo { x: to_fq(3) }).unwrap();
+    let proof = pk.prove(CubeDemo { x: to_fq(3) }, &mut rng).unwrap();
+    assert!(verify(&vk, &[to_fq(30)], &proof, &mut rng).unwrap());
+
+    // a modified circuit needs a new setup
+    assert!(pk.prove(FactorDemo { p: 3, q: 11 }, &mut rng).is_err());
+}
@@ -0,0 +1,184 @@
+use ark_bls12_381::Fr;
+use ark_ff::{Field, PrimeField};
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+
+use crate::compare::{enforce_bit_length, enforce_signed_bit_length};
+use crate::to_fq;
+
+// fixed-point numbers are integers scaled by 2^SCALE_BITS, mapped to the field with `to_fq`
+pub const SCALE_BITS: usize = 16;
+
+// fixed-point values (weights, features, outputs) are signed VALUE_BITS-bit integers
+pub const VALUE_BITS: usize = 48;
+
+pub fn to_fixed(x: f64) -> i64 {
+    (x * (1u64 << SCALE_BITS) as f64).round() as i64
+}
+
+pub fn from_fixed(x: i64) -> f64 {
+    x as f64 / (1u64 << SCALE_BITS) as f64
+}
+
+// native reference: floor(a * b / 2^SCALE_BITS)
+pub fn fixed_mul(a: i64, b: i64) -> i64 {
+    ((a as i128 * b as i128) >> SCALE_BITS) as i64
+}
+
+// native reference: floor(sum_i w_i * x_i / 2^SCALE_BITS) + bias, truncated once after the sum
+pub fn linear_model(weights: &[i64], bias: i64, features: &[i64]) -> i64 {
+    let sum: i128 = weights.iter().zip(features).map(|(&w, &x)| w as i128 * x as i128).sum();
+    (sum >> SCALE_BITS) as i64 + bias
+}
+
+// inverse of `to_fq` for field elements that encode an i128
+fn to_i128(x: Fr) -> i128 {
+    let positive = x.into_repr().0;
+    if positive[2] == 0 && positive[3] == 0 {
+        return (positive[0] as u128 | (positive[1] as u128) << 64) as i128;
+    }
+    let negative = (-x).into_repr().0;
+    -((negative[0] as u128 | (negative[1] as u128) << 64) as i128)
+}
+
+// `to_fq` for i128
+fn from_i128(x: i128) -> Fr {
+    let value = Fr::from(x.unsigned_abs());
+    if x < 0 { -value } else { value }
+}
+
+// floor(x / 2^SCALE_BITS) for a signed x of at most `bits` bits:
+// x = q * 2^SCALE_BITS + r with 0 <= r < 2^SCALE_BITS and q a signed (bits - SCALE_BITS)-bit integer
+pub fn truncate(x: &FpVar<Fr>, bits: usize) -> Result<FpVar<Fr>, SynthesisError> {
+    let cs = x.cs();
+    let mode = if cs.is_none() { AllocationMode::Constant } else { AllocationMode::Witness };
+    let value = x.value().ok().map(to_i128);
+
+    let quotient = FpVar::new_variable(
+        ark_relations::ns!(cs, "quotient"),
+        || value.map(|v| from_i128(v >> SCALE_BITS)).ok_or(SynthesisError::AssignmentMissing),
+        mode,
+    )?;
+    let remainder = FpVar::new_variable(
+        ark_relations::ns!(cs, "remainder"),
+        || value.map(|v| Fr::from((v & ((1 << SCALE_BITS) - 1)) as u64)).ok_or(SynthesisError::AssignmentMissing),
+        mode,
+    )?;
+
+    enforce_signed_bit_length(&quotient, bits - SCALE_BITS)?;
+    enforce_bit_length(&remainder, SCALE_BITS)?;
+
+    let scale = FpVar::constant(Fr::from(2u64).pow([SCALE_BITS as u64]));
+    (&quotient * scale + &remainder).enforce_equal(x)?;
+
+    Ok(quotient)
+}
+
+// scaled multiplication of two VALUE_BITS-bit fixed-point values
+pub fn fixed_mul_gadget(a: &FpVar<Fr>, b: &FpVar<Fr>) -> Result<FpVar<Fr>, SynthesisError> {
+    truncate(&(a * b), 2 * VALUE_BITS)
+}
+
+// proving that the public output is the hidden linear model applied to the public features
+#[derive(Clone)]
+pub struct LinearModelDemo {
+    pub weights: Vec<i64>,
+    pub bias: i64,
+    pub features: Vec<i64>,
+    pub output: i64,
+}
+
+impl ConstraintSynthesizer<Fr> for LinearModelDemo {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+
+        let features = Vec::<FpVar<Fr>>::new_input(
+            ark_relations::ns!(cs, "features"), || Ok(self.features.iter().map(|&x| to_fq(x)).collect::<Vec<_>>())
+        )?;
+        let output = FpVar::<Fr>::new_input(ark_relations::ns!(cs, "output"), || Ok(to_fq(self.output)))?;
+
+        let weights = Vec::<FpVar<Fr>>::new_witness(
+            ark_relations::ns!(cs, "weights"), || Ok(self.weights.iter().map(|&w| to_fq(w)).collect::<Vec<_>>())
+        )?;
+        let bias = FpVar::<Fr>::new_witness(ark_relations::ns!(cs, "bias"), || Ok(to_fq(self.bias)))?;
+
+        for x in features.iter().chain(weights.iter()).chain([&bias]) {
+            enforce_signed_bit_length(x, VALUE_BITS)?;
+        }
+
+        let mut sum = FpVar::<Fr>::zero();
+        for (w, x) in weights.iter().zip(features.iter()) {
+            sum += w * x;
+        }
+
+        // each product has 2 * VALUE_BITS bits, the sum needs a few more for the carries
+        let carry_bits = (usize::BITS - self.weights.len().leading_zeros()) as usize;
+        let result = truncate(&sum, 2 * VALUE_BITS + carry_bits)? + bias;
+        result.enforce_equal(&output)?;
+
+        Ok(())
+    }
+}
+
+// the verifier statement: features, then the output
+pub fn public_input(features: &[i64], output: i64) -> Vec<Fr> {
+    features.iter().chain([&output]).map(|&x| to_fq(x)).collect()
+}
+
+#[test]
+fn test_fixed_mul_matches_native() {
+    use ark_relations::r1cs::ConstraintSystem;
+
+    let values = [0.0, 1.0, -1.0, 0.5, -0.25, 3.75, -12.125, 1000.0 / 3.0];
+    for &a in values.iter() {
+        for &b in values.iter() {
+            let (a, b) = (to_fixed(a), to_fixed(b));
+
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let a_var = FpVar::<Fr>::new_witness(cs.clone(), || Ok(to_fq(a))).unwrap();
+            let b_var = FpVar::<Fr>::new_witness(cs.clone(), || Ok(to_fq(b))).unwrap();
+            let product = fixed_mul_gadget(&a_var, &b_var).unwrap();
+
+            assert_eq!(product.value().unwrap(), to_fq(fixed_mul(a, b)));
+            assert!(cs.is_satisfied().unwrap());
+        }
+    }
+
+    assert_eq!(fixed_mul(to_fixed(1.5), to_fixed(-2.0)), to_fixed(-3.0));
+    assert_eq!(from_fixed(fixed_mul(to_fixed(0.5), to_fixed(0.5))), 0.25);
+}
+
+#[test]
+fn test_linear_model_proof() {
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use ark_bls12_381::Bls12_381;
+    use ark_groth16::*;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let weights: Vec<i64> = [0.75, -1.5, 2.0].iter().map(|&w| to_fixed(w)).collect();
+    let bias = to_fixed(0.1);
+    let features: Vec<i64> = [4.0, 2.5, -0.125].iter().map(|&x| to_fixed(x)).collect();
+    let output = linear_model(&weights, bias, &features);
+    // 3 - 3.75 - 0.25 + 0.1
+    assert!((from_fixed(output) - (-0.9)).abs() < 1e-4);
+
+    let circuit = LinearModelDemo { weights: weights.clone(), bias, features: features.clone(), output };
+
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    circuit.clone().generate_constraints(cs.clone()).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+
+    let param = generate_random_parameters::<Bls12_381, _, _>(circuit.clone(), &mut rng).unwrap();
+    let proof = create_random_proof(circuit.clone(), &param, &mut rng).unwrap();
+    let pvk = prepare_verifying_key(&param.vk);
+
+    assert!(verify_proof(&pvk, &proof, &public_input(&features, output)).unwrap());
+    assert!(!verify_proof(&pvk, &proof, &public_input(&features, output + 1)).unwrap());
+
+    // a different model does not give this output
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    LinearModelDemo { bias: bias + 1, ..circuit }.generate_constraints(cs.clone()).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+}
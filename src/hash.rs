@@ -1,24 +1,20 @@
 
-use ark_bls12_381::{Bls12_381, Fr};
+use ark_bls12_381::Fr;
 use ark_ed_on_bls12_381::EdwardsAffine;
-use ark_r1cs_std::prelude::*;
 use ark_r1cs_std::uint8::UInt8;
-use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError, Field}; 
-use ark_r1cs_std::{fields::fp::FpVar, alloc::AllocVar};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_r1cs_std::alloc::AllocVar;
 use ark_r1cs_std::eq::EqGadget;
 use ark_crypto_primitives::crh::injective_map::constraints::{
     PedersenCRHCompressorGadget, TECompressorGadget, 
 };
-use ark_crypto_primitives::crh::{ CRHGadget, CRH , pedersen::Parameters as PedersenParamsVar2 ,  pedersen::constraints::{CRHParametersVar as PedersenParamsVar}};
+use ark_crypto_primitives::crh::{ CRHGadget, CRH , pedersen::Parameters as PedersenParamsVar2};
 use ark_crypto_primitives::crh::{
     injective_map::{PedersenCRHCompressor, TECompressor},
     /*pedersen::constraints::CRHGadget,*/
     pedersen
 };
-use ark_ed_on_bls12_381::{constraints::EdwardsVar, EdwardsProjective as JubJub, EdwardsParameters, constraints::FqVar, EdwardsProjective};
-use arkworks_native_gadgets::prelude::ark_ff::Fp256;
-
-use crate::encode::encode_hex; // import Groth16 library
+use ark_ed_on_bls12_381::{constraints::EdwardsVar, EdwardsProjective as JubJub, EdwardsParameters, EdwardsProjective};
 
 // pub type TwoToOneHash = PedersenCRHCompressor<JubJub, EdwardsVar, Window>;
 
@@ -47,29 +43,22 @@ pub type HashGadget = PedersenCRHCompressorGadget<
 >;
 pub type ConstraintF = ark_ed_on_bls12_381::Fq;
 
-/// The root of the account Merkle tree.
-pub type Image = <TestCRHGadget as CRH>::Output;
-
-pub type Image3 = ark_crypto_primitives::crh::pedersen::CRH<JubJub, Window>;
-
-/// The R1CS equivalent of the the Merkle tree root.
+// The R1CS equivalent of the the Merkle tree root.
 // pub type ImageVar = <TestCRHGadget as CRHGadget<TestCRHGadget, ConstraintF>>::OutputVar;
 
 
 // pub type HashParamsVar = <TwoToOneHashGadget as CRHGadget<TestCRHGadget, ConstraintF>>::ParametersVar;
 
-type TestCRH = pedersen::CRH<JubJub, Window>;
-type TestCRHGadget = pedersen::constraints::CRHGadget<JubJub, EdwardsVar, Window>;
+pub(crate) type TestCRH = pedersen::CRH<JubJub, Window>;
+pub(crate) type TestCRHGadget = pedersen::constraints::CRHGadget<JubJub, EdwardsVar, Window>;
 
 // type Image2 = ark_crypto_primitives::crh::pedersen::CRH<JubJub, Window>::Output;
-type ImageVar2 = <TestCRHGadget as CRHGadget<pedersen::CRH<EdwardsVar, Window>, ConstraintF>>::OutputVar;
 // type ImageVar2 = <ark_crypto_primitives::crh::pedersen::constraints::CRHGadget<ark_ec::twisted_edwards_extended::GroupProjective<EdwardsParameters>, ark_r1cs_std::groups::curves::twisted_edwards::AffineVar<EdwardsParameters, FpVar<Fp256<ark_bls12_381::FrParameters>>>, Window> as CRHGadget<TestCRHGadget, ConstraintF>>::OutputVar;
 type Image2 = <ark_crypto_primitives::crh::pedersen::CRH<ark_ec::twisted_edwards_extended::GroupProjective<EdwardsParameters>, Window> as CRH>::Output;
 
 // type a = CRHGadget<JubJub, EdwardsVar, Window>;
 
 pub type TwoToOneHash = PedersenCRHCompressor<EdwardsProjective, TECompressor, Window>;
-type ImageVar4 = <HashGadget as CRHGadget<TwoToOneHash, ConstraintF>>::OutputVar;
 
 // type Image5 = EdwardsVar<EdwardsParameters,>;
 
@@ -112,49 +101,38 @@ impl ConstraintSynthesizer<Fr> for HashDemo {
     }
 }
 
-// map i64 to a finite field Fp256
-fn to_fq(x: i64) -> Fr {
-    // get the positive value of x
-    let val:u64 = i64::unsigned_abs(x); 
-    // map integer to Fp256
-    let mut fq: Fr = val.into();  
-    if x< 0 { 
-        // let modulus = ark_bls12_381::FrParameters::MODULUS;
-        // println!("{:#?}", modu);
-        // if x is negative, we should return the inverse value
-        fq = - fq;   // neg_fq = modulus - fq
-    }  
-    fq
+// the verifier statement: the image (x, y), then the preimage bits, least significant bit of each byte first
+pub fn public_input(input: &[u8], image: &EdwardsAffine) -> Vec<Fr> {
+    let mut statement = vec![image.x, image.y];
+    for byte in input {
+        statement.extend((0..8).map(|i| Fr::from((byte >> i) & 1)));
+    }
+    statement
 }
 
-
-
 #[test]
 fn test_cube_proof(){
-    use ark_std::rand::{rngs::StdRng, SeedableRng, Rng};
+    use ark_bls12_381::Bls12_381;
     use ark_groth16::*;
-    use ark_ec::PairingEngine;
-    use arkworks_native_gadgets::{to_field_elements, from_field_elements};
+    use arkworks_native_gadgets::from_field_elements;
     use ark_serialize::*;
     use crate::encode;
+    use crate::rng::ProtocolRng;
 
-    let rng = &mut StdRng::seed_from_u64(0u64);
-
-    let x = to_fq(3);
+    // reproducible on purpose, the artifacts carry a warning
+    let mut rng = ProtocolRng::insecure_seed(0);
 
-    let input = Vec::new();
-    input.push(30);
+    let input = b"hello world".to_vec();
 
-    let parameters = TestCRH::setup(rng).unwrap();
+    let parameters = TestCRH::setup(&mut rng).unwrap();
     let primitive_result = TestCRH::evaluate(&parameters, input.as_slice()).unwrap();
+    let statement = public_input(&input, &primitive_result);
     let circuit = HashDemo {
         input,
         params: parameters,
         image: primitive_result,
     };
 
-    let mut statement = Vec::new();
-    statement.push(to_fq(30));
     let public_input = from_field_elements(&statement).unwrap();
     println!("public_input: {:?}", public_input);
 
@@ -172,9 +150,19 @@ fn test_cube_proof(){
     let pvk = prepare_verifying_key(&param.vk);
 
 
-    encode::encode_parameters(proof_vec, vkey_vec, public_input);
+    // into a scratch directory, not the tracked ./file artifacts
+    let dir = std::env::temp_dir().join("arkworks-example-encode-test");
+    encode::encode_parameters(&dir, proof_vec, vkey_vec, public_input, rng.warning()).unwrap();
+    let proof_hex = std::fs::read_to_string(dir.join("proof.txt")).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(proof_hex.starts_with("# WARNING: generated with --insecure-seed 0"));
 
     let result = verify_proof(&pvk, &proof, &statement).unwrap();
     println!("verify result is {:?}", result);
+    assert!(result);
 
+    // a different claimed image is rejected
+    let mut wrong = statement.clone();
+    wrong[0] += Fr::from(1u64);
+    assert!(!verify_proof(&pvk, &proof, &wrong).unwrap());
 }
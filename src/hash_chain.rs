@@ -0,0 +1,104 @@
+use ark_bls12_381::Fr;
+use ark_crypto_primitives::crh::{pedersen, CRHGadget, CRH};
+use ark_crypto_primitives::Error;
+use ark_ed_on_bls12_381::EdwardsProjective as JubJub;
+use ark_ff::to_bytes;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+
+use crate::hash::{ConstraintF, HashGadget, TwoToOneHash};
+
+// native reference: h_0 = seed, h_i+1 = H(h_i), with H the `hash.rs` Pedersen compressor
+pub fn hash_chain(params: &pedersen::Parameters<JubJub>, seed: Fr, num_steps: usize) -> Result<Fr, Error> {
+    let mut h = seed;
+    for _ in 0..num_steps {
+        h = <TwoToOneHash as CRH>::evaluate(params, &to_bytes![h]?)?;
+    }
+    Ok(h)
+}
+
+// proving that the public output is the num_steps-fold hash of a secret seed
+#[derive(Clone)]
+pub struct HashChainDemo {
+    pub params: pedersen::Parameters<JubJub>,
+    pub seed: Fr,
+    pub num_steps: usize,
+}
+
+impl ConstraintSynthesizer<Fr> for HashChainDemo {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+
+        let output = FpVar::<Fr>::new_input(
+            ark_relations::ns!(cs, "output"),
+            || hash_chain(&self.params, self.seed, self.num_steps).map_err(|_| SynthesisError::AssignmentMissing)
+        )?;
+
+        let params = pedersen::constraints::CRHParametersVar::new_constant(
+            ark_relations::ns!(cs, "parameters"), &self.params
+        )?;
+
+        let mut h = FpVar::<Fr>::new_witness(ark_relations::ns!(cs, "seed"), || Ok(self.seed))?;
+        for _ in 0..self.num_steps {
+            h = <HashGadget as CRHGadget<TwoToOneHash, ConstraintF>>::evaluate(&params, &h.to_bytes()?)?;
+        }
+
+        h.enforce_equal(&output)?;
+
+        Ok(())
+    }
+}
+
+// number of constraints for a chain of num_steps hashes, see also `stats hash-chain --size <steps>`
+#[cfg(test)]
+pub fn num_constraints(params: &pedersen::Parameters<JubJub>, num_steps: usize) -> usize {
+    use ark_relations::r1cs::{ConstraintSystem, SynthesisMode};
+
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    cs.set_mode(SynthesisMode::Setup);
+    let circuit = HashChainDemo {
+        params: params.clone(),
+        seed: Fr::from(0u64),
+        num_steps,
+    };
+    circuit.generate_constraints(cs.clone()).unwrap();
+    cs.num_constraints()
+}
+
+#[test]
+fn test_hash_chain_proof() {
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use ark_std::UniformRand;
+    use ark_bls12_381::Bls12_381;
+    use ark_groth16::*;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let params = <TwoToOneHash as CRH>::setup(&mut rng).unwrap();
+    let seed = Fr::rand(&mut rng);
+    let output = hash_chain(&params, seed, 8).unwrap();
+    assert_eq!(hash_chain(&params, hash_chain(&params, seed, 3).unwrap(), 5).unwrap(), output);
+
+    let circuit = HashChainDemo { params: params.clone(), seed, num_steps: 8 };
+
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    circuit.clone().generate_constraints(cs.clone()).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+
+    let param = generate_random_parameters::<Bls12_381, _, _>(circuit.clone(), &mut rng).unwrap();
+    let proof = create_random_proof(circuit, &param, &mut rng).unwrap();
+    let pvk = prepare_verifying_key(&param.vk);
+
+    assert!(verify_proof(&pvk, &proof, &[output]).unwrap());
+    // one step short
+    assert!(!verify_proof(&pvk, &proof, &[hash_chain(&params, seed, 7).unwrap()]).unwrap());
+
+    // the cost is linear in the number of steps
+    let per_step = num_constraints(&params, 1) - num_constraints(&params, 0);
+    for num_steps in [1, 10, 100] {
+        let count = num_constraints(&params, num_steps);
+        assert_eq!(count, num_constraints(&params, 0) + num_steps * per_step);
+        println!("{} steps: {} constraints", num_steps, count);
+    }
+}
@@ -0,0 +1,179 @@
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_crypto_primitives::Error;
+use ark_r1cs_std::prelude::*;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_relations::r1cs::{
+    ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, SynthesisError, SynthesisMode,
+};
+
+// native reference: (a, b) -> (b, a + b) applied num_steps times, returns the last term
+#[cfg(test)]
+pub fn fibonacci(a: Fr, b: Fr, num_steps: usize) -> Fr {
+    let (mut a, mut b) = (a, b);
+    for _ in 0..num_steps {
+        let next = a + b;
+        a = b;
+        b = next;
+    }
+    b
+}
+
+// native reference: x -> x^3 + x applied num_steps times
+pub fn iterate_cube(x: Fr, num_steps: usize) -> Fr {
+    let mut x = x;
+    for _ in 0..num_steps {
+        x = x * x * x + x;
+    }
+    x
+}
+
+// proving that out is the num_steps-th Fibonacci term after the public a, b
+#[cfg(test)]
+#[derive(Clone)]
+pub struct FibonacciDemo {
+    pub a: Fr,
+    pub b: Fr,
+    pub num_steps: usize,
+}
+
+#[cfg(test)]
+impl ConstraintSynthesizer<Fr> for FibonacciDemo {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+
+        let mut a = FpVar::<Fr>::new_input(ark_relations::ns!(cs, "initial a"), || Ok(self.a))?;
+        let mut b = FpVar::<Fr>::new_input(ark_relations::ns!(cs, "initial b"), || Ok(self.b))?;
+        let out = FpVar::<Fr>::new_input(
+            ark_relations::ns!(cs, "final value"), || Ok(fibonacci(self.a, self.b, self.num_steps))
+        )?;
+
+        // every term is a fresh witness so that each step costs one constraint
+        for _ in 0..self.num_steps {
+            let next = FpVar::<Fr>::new_witness(
+                ark_relations::ns!(cs, "next term"), || Ok(a.value()? + b.value()?)
+            )?;
+            next.enforce_equal(&(&a + &b))?;
+            a = b;
+            b = next;
+        }
+
+        out.enforce_equal(&b)?;
+
+        Ok(())
+    }
+}
+
+// proving that out is x -> x^3 + x applied num_steps times to the public x
+#[derive(Clone)]
+pub struct IteratedCubeDemo {
+    pub x: Fr,
+    pub num_steps: usize,
+}
+
+impl ConstraintSynthesizer<Fr> for IteratedCubeDemo {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+
+        let mut x = FpVar::<Fr>::new_input(ark_relations::ns!(cs, "initial x"), || Ok(self.x))?;
+        let out = FpVar::<Fr>::new_input(
+            ark_relations::ns!(cs, "final value"), || Ok(iterate_cube(self.x, self.num_steps))
+        )?;
+
+        // same two multiplications per step as `CubeDemo`
+        for _ in 0..self.num_steps {
+            let square = FpVar::<Fr>::new_witness(
+                ark_relations::ns!(cs, "new witness x^2"), || x.value().map(|x| x * x)
+            )?;
+            x.square_equals(&square)?;
+
+            let cube = FpVar::<Fr>::new_witness(
+                ark_relations::ns!(cs, "new witness x^3"), || Ok(square.value()? * x.value()?)
+            )?;
+            square.mul_equals(&x, &cube)?;
+
+            x = cube + x;
+        }
+
+        out.enforce_equal(&x)?;
+
+        Ok(())
+    }
+}
+
+// constraint count, setup and proving time of `IteratedCubeDemo` for each step count, for
+// `arkworks-example bench`
+pub fn benchmark_iterated_cube(step_counts: &[usize]) -> Result<(), Error> {
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use ark_groth16::*;
+    use std::time::Instant;
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    for &num_steps in step_counts {
+        let circuit = IteratedCubeDemo { x: Fr::from(3u64), num_steps };
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        cs.set_mode(SynthesisMode::Setup);
+        circuit.clone().generate_constraints(cs.clone())?;
+
+        let start = Instant::now();
+        let param = generate_random_parameters::<Bls12_381, _, _>(circuit.clone(), &mut rng)?;
+        let setup_time = start.elapsed();
+
+        let start = Instant::now();
+        let _proof = create_random_proof(circuit, &param, &mut rng)?;
+        let prove_time = start.elapsed();
+
+        println!(
+            "steps: {:>6}  constraints: {:>7}  setup: {:?}  prove: {:?}",
+            num_steps, cs.num_constraints(), setup_time, prove_time
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn test_fibonacci_proof() {
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use ark_groth16::*;
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let (a, b) = (Fr::from(0u64), Fr::from(1u64));
+    let circuit = FibonacciDemo { a, b, num_steps: 10 };
+    // 0, 1, 1, 2, 3, 5, 8, 13, 21, 34, 55, 89
+    let out = fibonacci(a, b, 10);
+    assert_eq!(out, Fr::from(89u64));
+
+    let param = generate_random_parameters::<Bls12_381, _, _>(circuit.clone(), &mut rng).unwrap();
+    let proof = create_random_proof(circuit, &param, &mut rng).unwrap();
+    let pvk = prepare_verifying_key(&param.vk);
+
+    assert!(verify_proof(&pvk, &proof, &[a, b, out]).unwrap());
+    assert!(!verify_proof(&pvk, &proof, &[a, b, Fr::from(88u64)]).unwrap());
+}
+
+#[test]
+fn test_iterated_cube_proof() {
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use ark_groth16::*;
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    // a single step is `CubeDemo`: 3^3 + 3 == 30
+    assert_eq!(iterate_cube(Fr::from(3u64), 1), Fr::from(30u64));
+
+    let x = Fr::from(3u64);
+    let circuit = IteratedCubeDemo { x, num_steps: 20 };
+
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    circuit.clone().generate_constraints(cs.clone()).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+    assert_eq!(cs.num_constraints(), 2 * 20 + 1);
+
+    let param = generate_random_parameters::<Bls12_381, _, _>(circuit.clone(), &mut rng).unwrap();
+    let proof = create_random_proof(circuit, &param, &mut rng).unwrap();
+    let pvk = prepare_verifying_key(&param.vk);
+
+    assert!(verify_proof(&pvk, &proof, &[x, iterate_cube(x, 20)]).unwrap());
+
+    benchmark_iterated_cube(&[1, 10, 100]).unwrap();
+}
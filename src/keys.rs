@@ -0,0 +1,442 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use ark_crypto_primitives::Error;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::rand::{CryptoRng, RngCore};
+use blake2::{Blake2s, Digest};
+
+use crate::backend::{self, Backend, MarlinSrs, ProvingKey, VerifyingKey};
+use crate::fingerprint::BoundProvingKey;
+
+// key files start with a header naming what the key is for, followed by the `ark_serialize` payload:
+//   MAGIC, kind, backend, curve, circuit id, circuit fingerprint, compression, insecure seed, hash, payload
+// where hash is blake2s of everything before it and the payload, so the header cannot be edited
+// either. Loading a key checks all of them against what the caller expects; the fingerprint
+// is checked when proving, see `fingerprint::BoundProvingKey`. The insecure seed is set for keys
+// from a setup run with --insecure-seed, so their warning is not lost once they are saved
+const MAGIC: &[u8; 8] = b"ARKWKEY1";
+
+pub const CURVE: &str = "bls12-381";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Compression {
+    Compressed,
+    Uncompressed,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KeyKind {
+    Proving,
+    Verifying,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyHeader {
+    pub kind: KeyKind,
+    pub backend: Backend,
+    pub curve: String,
+    pub circuit_id: String,
+    pub fingerprint: Vec<u8>,
+    pub compression: Compression,
+    pub insecure_seed: Option<u64>,
+    pub hash: Vec<u8>,
+}
+
+fn write_string<W: Write>(writer: &mut W, s: &str) -> Result<(), Error> {
+    Ok(s.as_bytes().to_vec().serialize(writer)?)
+}
+
+fn read_string<R: Read>(reader: &mut R) -> Result<String, Error> {
+    Ok(String::from_utf8(Vec::<u8>::deserialize(reader)?)?)
+}
+
+impl KeyHeader {
+    // the header of a key for this curve, hashed by `write_key`
+    fn new(
+        kind: KeyKind,
+        backend: Backend,
+        circuit_id: &str,
+        fingerprint: &[u8],
+        compression: Compression,
+        insecure_seed: Option<u64>,
+    ) -> Self {
+        KeyHeader {
+            kind,
+            backend,
+            curve: CURVE.to_string(),
+            circuit_id: circuit_id.to_string(),
+            fingerprint: fingerprint.to_vec(),
+            compression,
+            insecure_seed,
+            hash: vec![],
+        }
+    }
+
+    // everything but the hash
+    fn write_fields<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        writer.write_all(MAGIC)?;
+        (self.kind == KeyKind::Verifying).serialize(&mut *writer)?;
+        write_string(writer, &self.backend.to_string())?;
+        write_string(writer, &self.curve)?;
+        write_string(writer, &self.circuit_id)?;
+        self.fingerprint.serialize(&mut *writer)?;
+        (self.compression == Compression::Compressed).serialize(&mut *writer)?;
+        self.insecure_seed.serialize(&mut *writer)?;
+        Ok(())
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        self.write_fields(writer)?;
+        self.hash.serialize(&mut *writer)?;
+        Ok(())
+    }
+
+    fn digest(&self, payload: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut bytes = vec![];
+        self.write_fields(&mut bytes)?;
+        bytes.extend_from_slice(payload);
+        Ok(Blake2s::digest(&bytes).to_vec())
+    }
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err("not a key file".into());
+        }
+        let kind = if bool::deserialize(&mut *reader)? { KeyKind::Verifying } else { KeyKind::Proving };
+        let backend = read_string(reader)?.parse::<Backend>()?;
+        let curve = read_string(reader)?;
+        let circuit_id = read_string(reader)?;
+        let fingerprint = Vec::<u8>::deserialize(&mut *reader)?;
+        let compression = if bool::deserialize(&mut *reader)? { Compression::Compressed } else { Compression::Uncompressed };
+        let insecure_seed = Option::<u64>::deserialize(&mut *reader)?;
+        let hash = Vec::<u8>::deserialize(&mut *reader)?;
+        Ok(KeyHeader { kind, backend, curve, circuit_id, fingerprint, compression, insecure_seed, hash })
+    }
+
+    // rejects a key of the wrong kind, or made for another curve or circuit
+    fn check(&self, kind: KeyKind, circuit_id: &str) -> Result<(), Error> {
+        if self.kind != kind {
+            return Err(format!("expected a {:?} key, found a {:?} key", kind, self.kind).into());
+        }
+        if self.curve != CURVE {
+            return Err(format!("key is for curve {}, not {}", self.curve, CURVE).into());
+        }
+        if self.circuit_id != circuit_id {
+            return Err(format!("key was generated for circuit {:?}, not {:?}", self.circuit_id, circuit_id).into());
+        }
+        Ok(())
+    }
+}
+
+fn serialize_payload<T: CanonicalSerialize>(value: &T, compression: Compression) -> Result<Vec<u8>, Error> {
+    let mut bytes = vec![];
+    match compression {
+        Compression::Compressed => value.serialize(&mut bytes)?,
+        Compression::Uncompressed => value.serialize_uncompressed(&mut bytes)?,
+    }
+    Ok(bytes)
+}
+
+fn deserialize_payload<T: CanonicalDeserialize>(mut bytes: &[u8], compression: Compression) -> Result<T, Error> {
+    let value = match compression {
+        Compression::Compressed => T::deserialize(&mut bytes)?,
+        Compression::Uncompressed => T::deserialize_uncompressed(&mut bytes)?,
+    };
+    if !bytes.is_empty() {
+        return Err("trailing bytes after the key".into());
+    }
+    Ok(value)
+}
+
+// `header.hash` is filled in here, from the rest of the header and the payload
+fn write_key<W: Write>(mut writer: W, mut header: KeyHeader, payload: Vec<u8>) -> Result<(), Error> {
+    header.hash = header.digest(&payload)?;
+    header.write(&mut writer)?;
+    payload.serialize(&mut writer)?;
+    Ok(())
+}
+
+// the checked header and the payload it describes
+fn read_key<R: Read>(mut reader: R, kind: KeyKind, circuit_id: &str) -> Result<(KeyHeader, Vec<u8>), Error> {
+    let header = KeyHeader::read(&mut reader)?;
+    header.check(kind, circuit_id)?;
+    let payload = Vec::<u8>::deserialize(&mut reader)?;
+    if header.digest(&payload)? != header.hash {
+        return Err("key header or content does not match its hash".into());
+    }
+    Ok((header, payload))
+}
+
+pub fn write_proving_key<W: Write>(
+    writer: W,
+    pk: &BoundProvingKey,
+    circuit_id: &str,
+    compression: Compression,
+    insecure_seed: Option<u64>,
+) -> Result<(), Error> {
+    let payload = match &pk.pk {
+        ProvingKey::Groth16(pk) => serialize_payload(pk, compression)?,
+        ProvingKey::Gm17(pk) => serialize_payload(pk, compression)?,
+        ProvingKey::Marlin(pk) => serialize_payload(pk, compression)?,
+    };
+    let header = KeyHeader::new(KeyKind::Proving, pk.pk.backend(), circuit_id, &pk.fingerprint, compression, insecure_seed);
+    write_key(writer, header, payload)
+}
+
+pub fn read_proving_key<R: Read>(reader: R, circuit_id: &str) -> Result<(KeyHeader, BoundProvingKey), Error> {
+    let (header, payload) = read_key(reader, KeyKind::Proving, circuit_id)?;
+    let pk = match header.backend {
+        Backend::Groth16 => ProvingKey::Groth16(deserialize_payload(&payload, header.compression)?),
+        Backend::Gm17 => ProvingKey::Gm17(deserialize_payload(&payload, header.compression)?),
+        Backend::Marlin => ProvingKey::Marlin(deserialize_payload(&payload, header.compression)?),
+    };
+    let fingerprint = header.fingerprint.clone();
+    Ok((header, BoundProvingKey { pk, fingerprint }))
+}
+
+pub fn write_verifying_key<W: Write>(
+    writer: W,
+    vk: &VerifyingKey,
+    circuit_id: &str,
+    fingerprint: &[u8],
+    compression: Compression,
+    insecure_seed: Option<u64>,
+) -> Result<(), Error> {
+    let payload = match vk {
+        VerifyingKey::Groth16(vk) => serialize_payload(vk, compression)?,
+        VerifyingKey::Gm17(vk) => serialize_payload(vk, compression)?,
+        VerifyingKey::Marlin(vk) => serialize_payload(vk, compression)?,
+    };
+    let header = KeyHeader::new(KeyKind::Verifying, vk.backend(), circuit_id, fingerprint, compression, insecure_seed);
+    write_key(writer, header, payload)
+}
+
+pub fn read_verifying_key<R: Read>(reader: R, circuit_id: &str) -> Result<(KeyHeader, VerifyingKey), Error> {
+    let (header, payload) = read_key(reader, KeyKind::Verifying, circuit_id)?;
+    let vk = match header.backend {
+        Backend::Groth16 => VerifyingKey::Groth16(deserialize_payload(&payload, header.compression)?),
+        Backend::Gm17 => VerifyingKey::Gm17(deserialize_payload(&payload, header.compression)?),
+        Backend::Marlin => VerifyingKey::Marlin(deserialize_payload(&payload, header.compression)?),
+    };
+    Ok((header, vk))
+}
+
+// a key pair as `save_keys` stores it
+pub struct SavedKeys {
+    pub pk: BoundProvingKey,
+    pub vk: VerifyingKey,
+    // the seed of a setup run with --insecure-seed
+    pub insecure_seed: Option<u64>,
+}
+
+pub fn save_keys(dir: impl AsRef<Path>, circuit_id: &str, keys: &SavedKeys, compression: Compression) -> Result<(), Error> {
+    std::fs::create_dir_all(dir.as_ref())?;
+    let (pk_path, vk_path) = key_paths(dir.as_ref(), circuit_id, keys.pk.pk.backend());
+    let mut writer = BufWriter::new(File::create(pk_path)?);
+    write_proving_key(&mut writer, &keys.pk, circuit_id, compression, keys.insecure_seed)?;
+    writer.flush()?;
+    let mut writer = BufWriter::new(File::create(vk_path)?);
+    write_verifying_key(&mut writer, &keys.vk, circuit_id, &keys.pk.fingerprint, compression, keys.insecure_seed)?;
+    writer.flush()?;
+    Ok(())
+}
+
+// None if no keys were saved for this circuit and backend yet. Both keys are checked against
+// `circuit_id` on reading, and against each other here
+pub fn load_keys(dir: impl AsRef<Path>, circuit_id: &str, backend: Backend) -> Result<Option<SavedKeys>, Error> {
+    let (pk_path, vk_path) = key_paths(dir.as_ref(), circuit_id, backend);
+    if !pk_path.exists() || !vk_path.exists() {
+        return Ok(None);
+    }
+    let (pk_header, pk) = read_proving_key(BufReader::new(File::open(pk_path)?), circuit_id)?;
+    let (vk_header, vk) = read_verifying_key(BufReader::new(File::open(vk_path)?), circuit_id)?;
+    if pk_header.backend != backend || vk_header.backend != backend {
+        return Err(format!("saved keys are not for the {} backend", backend).into());
+    }
+    if pk_header.circuit_id != vk_header.circuit_id || pk_header.fingerprint != vk_header.fingerprint {
+        return Err("saved proving and verifying keys are for different circuits".into());
+    }
+    if pk_header.insecure_seed != vk_header.insecure_seed || pk.pk.verifying_key().to_bytes() != vk.to_bytes() {
+        return Err("saved proving and verifying keys are from different setups".into());
+    }
+    Ok(Some(SavedKeys { pk, vk, insecure_seed: pk_header.insecure_seed }))
+}
+
+// the Marlin universal SRS is saved once per key directory as SRS_MAGIC, insecure seed, hash,
+// payload, with hash the blake2s of the seed and the payload; every circuit is indexed against it
+const SRS_MAGIC: &[u8; 8] = b"ARKWSRS1";
+const SRS_FILE: &str = "marlin.srs";
+
+fn srs_digest(insecure_seed: Option<u64>, payload: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut bytes = vec![];
+    insecure_seed.serialize(&mut bytes)?;
+    bytes.extend_from_slice(payload);
+    Ok(Blake2s::digest(&bytes).to_vec())
+}
+
+pub fn write_srs<W: Write>(mut writer: W, srs: &MarlinSrs, insecure_seed: Option<u64>) -> Result<(), Error> {
+    let payload = serialize_payload(srs, Compression::Uncompressed)?;
+    writer.write_all(SRS_MAGIC)?;
+    insecure_seed.serialize(&mut writer)?;
+    srs_digest(insecure_seed, &payload)?.serialize(&mut writer)?;
+    payload.serialize(&mut writer)?;
+    Ok(())
+}
+
+pub fn read_srs<R: Read>(mut reader: R) -> Result<(MarlinSrs, Option<u64>), Error> {
+    let mut magic = [0u8; 8];
+    reader.read_exact(&mut magic)?;
+    if &magic != SRS_MAGIC {
+        return Err("not a universal SRS file".into());
+    }
+    let insecure_seed = Option::<u64>::deserialize(&mut reader)?;
+    let hash = Vec::<u8>::deserialize(&mut reader)?;
+    let payload = Vec::<u8>::deserialize(&mut reader)?;
+    if srs_digest(insecure_seed, &payload)? != hash {
+        return Err("universal SRS does not match its hash".into());
+    }
+    Ok((deserialize_payload(&payload, Compression::Uncompressed)?, insecure_seed))
+}
+
+// the SRS saved in `dir`, or else a new one of the configured maximum size, saved there so
+// that later circuits are only indexed against it. Also returns the seed the SRS was generated
+// with, if any: keys indexed against a seeded SRS are no safer than the SRS
+pub fn load_or_create_srs<R: RngCore + CryptoRng>(
+    dir: Option<&Path>,
+    insecure_seed: Option<u64>,
+    rng: &mut R,
+) -> Result<(MarlinSrs, Option<u64>), Error> {
+    let path = dir.map(|dir| dir.join(SRS_FILE));
+    if let Some(path) = path.as_ref().filter(|path| path.exists()) {
+        return read_srs(BufReader::new(File::open(path)?));
+    }
+    let srs = backend::universal_setup(
+        backend::MARLIN_MAX_CONSTRAINTS,
+        backend::MARLIN_MAX_VARIABLES,
+        backend::MARLIN_MAX_NON_ZERO,
+        rng,
+    )?;
+    if let (Some(dir), Some(path)) = (dir, path) {
+        std::fs::create_dir_all(dir)?;
+        let mut writer = BufWriter::new(File::create(path)?);
+        write_srs(&mut writer, &srs, insecure_seed)?;
+        writer.flush()?;
+    }
+    Ok((srs, insecure_seed))
+}
+
+fn key_paths(dir: &Path, circuit_id: &str, backend: Backend) -> (std::path::PathBuf, std::path::PathBuf) {
+    (
+        dir.join(format!("{}.{}.pk", circuit_id, backend)),
+        dir.join(format!("{}.{}.vk", circuit_id, backend)),
+    )
+}
+
+#[test]
+fn test_key_persistence() {
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use crate::backend::{setup, verify};
+    use crate::{to_fq, CubeDemo};
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let cube = CubeDemo { x: to_fq(3) };
+    let (pk, vk) = setup(Backend::Groth16, cube.clone(), None, &mut rng).unwrap();
+    let pk = BoundProvingKey::bind(pk, cube.clone()).unwrap();
+
+    let mut sizes = vec![];
+    for compression in [Compression::Compressed, Compression::Uncompressed] {
+        let mut pk_bytes = vec![];
+        write_proving_key(&mut pk_bytes, &pk, "cube", compression, None).unwrap();
+        let mut vk_bytes = vec![];
+        write_verifying_key(&mut vk_bytes, &vk, "cube", &pk.fingerprint, compression, None).unwrap();
+        sizes.push(pk_bytes.len());
+
+        let (_, loaded_pk) = read_proving_key(&pk_bytes[..], "cube").unwrap();
+        let (_, loaded_vk) = read_verifying_key(&vk_bytes[..], "cube").unwrap();
+        assert_eq!(loaded_pk.fingerprint, pk.fingerprint);
+        let proof = loaded_pk.prove(cube.clone(), &mut rng).unwrap();
+        assert!(verify(&loaded_vk, &[to_fq(30)], &proof, &mut rng).unwrap());
+
+        // a key for another circuit, or of the other kind, is rejected
+        assert!(read_proving_key(&pk_bytes[..], "factor").is_err());
+        assert!(read_verifying_key(&pk_bytes[..], "cube").is_err());
+
+        // so is a modified key
+        let mut corrupted = pk_bytes.clone();
+        let last = corrupted.len() - 1;
+        corrupted[last] ^= 1;
+        assert!(read_proving_key(&corrupted[..], "cube").is_err());
+
+        // or one whose header was edited, here the fingerprint of the circuit
+        let mut relabeled = pk_bytes.clone();
+        let at = relabeled.windows(pk.fingerprint.len()).position(|w| w == &pk.fingerprint[..]).unwrap();
+        relabeled[at] ^= 1;
+        assert!(read_proving_key(&relabeled[..], "cube").is_err());
+    }
+    assert!(sizes[0] < sizes[1]);
+
+    // keys on disk are found by circuit and backend
+    let dir = std::env::temp_dir().join("arkworks-example-keys-test");
+    assert!(load_keys(&dir, "cube", Backend::Gm17).unwrap().is_none());
+    // the directory is created if needed
+    let keys = SavedKeys { pk, vk, insecure_seed: Some(0) };
+    save_keys(&dir, "cube", &keys, Compression::Compressed).unwrap();
+    let loaded = load_keys(&dir, "cube", Backend::Groth16).unwrap().unwrap();
+    assert_eq!(loaded.pk.pk.backend(), Backend::Groth16);
+    assert_eq!(loaded.vk.to_bytes(), keys.vk.to_bytes());
+    // the seed of the setup is kept, so the warning is not lost
+    assert_eq!(loaded.insecure_seed, Some(0));
+
+    // a verifying key from another setup of the same circuit is not paired with the proving key
+    let (_, other_vk) = setup(Backend::Groth16, cube.clone(), None, &mut rng).unwrap();
+    let (_, vk_path) = key_paths(&dir, "cube", Backend::Groth16);
+    let mut writer = File::create(&vk_path).unwrap();
+    write_verifying_key(&mut writer, &other_vk, "cube", &keys.pk.fingerprint, Compression::Compressed, Some(0)).unwrap();
+    assert!(load_keys(&dir, "cube", Backend::Groth16).is_err());
+
+    // nor one labeled with another fingerprint, or another seed
+    let mut writer = File::create(&vk_path).unwrap();
+    write_verifying_key(&mut writer, &keys.vk, "cube", &[0u8; 32], Compression::Compressed, Some(0)).unwrap();
+    assert!(load_keys(&dir, "cube", Backend::Groth16).is_err());
+    let mut writer = File::create(&vk_path).unwrap();
+    write_verifying_key(&mut writer, &keys.vk, "cube", &keys.pk.fingerprint, Compression::Compressed, None).unwrap();
+    assert!(load_keys(&dir, "cube", Backend::Groth16).is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_srs_persistence() {
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use crate::backend::{prove, setup, verify};
+    use crate::{to_fq, CubeDemo, FactorDemo};
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let dir = std::env::temp_dir().join("arkworks-example-srs-test");
+    let (srs, _) = load_or_create_srs(Some(&dir), Some(0), &mut rng).unwrap();
+    // a second run reads the same SRS instead of generating another one, and knows it was seeded
+    let (loaded, seed) = load_or_create_srs(Some(&dir), None, &mut rng).unwrap();
+    assert_eq!(seed, Some(0));
+    let mut bytes = vec![];
+    std::fs::File::open(dir.join(SRS_FILE)).unwrap().read_to_end(&mut bytes).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(loaded.powers_of_g, srs.powers_of_g);
+
+    // both circuits index against it
+    let (pk, vk) = setup(Backend::Marlin, CubeDemo { x: to_fq(3) }, Some(&loaded), &mut rng).unwrap();
+    let proof = prove(&pk, CubeDemo { x: to_fq(3) }, &mut rng).unwrap();
+    assert!(verify(&vk, &[to_fq(30)], &proof, &mut rng).unwrap());
+    let (pk, vk) = setup(Backend::Marlin, FactorDemo { p: 3, q: 11 }, Some(&loaded), &mut rng).unwrap();
+    let proof = prove(&pk, FactorDemo { p: 3, q: 11 }, &mut rng).unwrap();
+    assert!(verify(&vk, &[to_fq(33)], &proof, &mut rng).unwrap());
+
+    // a modified SRS is rejected
+    let last = bytes.len() - 1;
+    bytes[last] ^= 1;
+    assert!(read_srs(&bytes[..]).is_err());
+}
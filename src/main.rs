@@ -1,49 +1,88 @@
 // mod cube;
+mod backend;
+#[cfg(test)]
+mod blake2s;
+mod ceremony;
+mod check;
+#[cfg(test)]
+mod commitment;
+mod compare;
+#[cfg(test)]
+mod elgamal;
 mod encode;
+mod fingerprint;
+#[cfg(test)]
+mod fixed;
 mod hash;
+mod hash_chain;
+mod iterate;
+mod keys;
+mod matrix;
+#[cfg(test)]
+mod membership;
+mod phase1;
+#[cfg(test)]
+mod recursion;
+mod rng;
+#[cfg(test)]
+mod schnorr;
+mod sha256;
+mod stats;
+#[cfg(test)]
+mod sudoku;
+#[cfg(test)]
+mod transfer;
+#[cfg(test)]
+mod voting;
 
-use ark_bls12_381::{Bls12_381, Fr};
+use ark_bls12_381::Fr;
+use ark_ff::PrimeField;
 use ark_r1cs_std::prelude::FieldVar;
-use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError, Field}; 
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
 use ark_r1cs_std::{fields::fp::FpVar, alloc::AllocVar};
 use ark_r1cs_std::eq::EqGadget;
-use ark_crypto_primitives::snark::*;
+use ark_r1cs_std::prelude::Boolean;
+use ark_crypto_primitives::Error;
 
-use crate::encode::encode_hex; // import Groth16 library
+use crate::backend::Backend;
+use crate::fingerprint::BoundProvingKey;
+use crate::keys::Compression;
+use crate::rng::ProtocolRng;
 
 // proving that I know x such that x^3 + x + 5 == 35
 // Generalized: x^3 + x + 5 == out
+// over BLS12-381 by default, `recursion` proves it over BLS12-377
 #[allow(clippy::upper_case_acronyms)]
 #[derive(Clone)]
-pub struct CubeDemo {
-    pub x: Fr,
+pub struct CubeDemo<F: PrimeField = Fr> {
+    pub x: F,
 }
 
-impl ConstraintSynthesizer<Fr> for CubeDemo { 
-    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+impl<F: PrimeField> ConstraintSynthesizer<F> for CubeDemo<F> { 
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
 
         // x is the witness which should be hidden
-        let x_witness = FpVar::<Fr>::new_witness(
+        let x_witness = FpVar::<F>::new_witness(
             ark_relations::ns!(cs, "new witness x"), || Ok(self.x)
         ).expect("create new witness"); 
 
         let x_val = self.x;
         let tmp_square = x_val * x_val;
-        let square_witness = FpVar::<Fr>::new_witness(
+        let square_witness = FpVar::<F>::new_witness(
             ark_relations::ns!(cs, "new witness x^2"), || Ok(tmp_square)
         ).expect("create new witness");
 
         x_witness.square_equals(&square_witness)?;
 
        let tmp_cube = tmp_square * x_val;
-       let cube_witness = FpVar::<Fr>::new_witness(
+       let cube_witness = FpVar::<F>::new_witness(
         ark_relations::ns!(cs, "new witness x^3"), || Ok(tmp_cube)
         ).expect("create new witness");
 
         square_witness.mul_equals(&x_witness, &cube_witness)?;
 
         let tmp_out = tmp_cube + x_val;
-        let out = FpVar::<Fr>::new_input(
+        let out = FpVar::<F>::new_input(
             ark_relations::ns!(cs, "new witness x^3 + x"), || Ok(tmp_out)
         ).expect("create new witness");
 
@@ -53,6 +92,40 @@ impl ConstraintSynthesizer<Fr> for CubeDemo {
     }
 }
 
+// proving that I know non-trivial factors p, q of N
+// p, q have at most FACTOR_BITS bits, so p * q cannot wrap around the field,
+// and 2 <= p, q, so neither of them is 1 or N
+pub const FACTOR_BITS: usize = 64;
+
+#[derive(Clone)]
+pub struct FactorDemo {
+    pub p: u64,
+    pub q: u64,
+}
+
+impl ConstraintSynthesizer<Fr> for FactorDemo {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+
+        let n = FpVar::<Fr>::new_input(
+            ark_relations::ns!(cs, "new input N"), || Ok(Fr::from(self.p as u128 * self.q as u128))
+        )?;
+
+        // p and q are the witnesses which should be hidden
+        let p_witness = FpVar::<Fr>::new_witness(ark_relations::ns!(cs, "new witness p"), || Ok(Fr::from(self.p)))?;
+        let q_witness = FpVar::<Fr>::new_witness(ark_relations::ns!(cs, "new witness q"), || Ok(Fr::from(self.q)))?;
+
+        let two = FpVar::constant(to_fq(2));
+        for factor in [&p_witness, &q_witness] {
+            compare::enforce_bit_length(factor, FACTOR_BITS)?;
+            compare::is_greater_or_equal(factor, &two, FACTOR_BITS)?.enforce_equal(&Boolean::TRUE)?;
+        }
+
+        p_witness.mul_equals(&q_witness, &n)?;
+
+        Ok(())
+    }
+}
+
 // map i64 to a finite field Fp256
 fn to_fq(x: i64) -> Fr {
     // get the positive value of x
@@ -68,44 +141,331 @@ fn to_fq(x: i64) -> Fr {
     fq
 }
 
-fn main() {
-    use ark_std::rand::{rngs::StdRng, SeedableRng};
-    use ark_groth16::*;
-    use ark_ec::PairingEngine;
-    use arkworks_native_gadgets::{to_field_elements, from_field_elements};
-    use ark_serialize::*;
-    use crate::encode;
+// what `run` does with a circuit
+#[derive(Clone, Copy, PartialEq)]
+enum Command {
+    Prove,
+    Stats,
+    Check,
+}
 
-    let mut rng = StdRng::seed_from_u64(0u64);
+// how `run` sets up, proves and stores its artifacts
+struct Options {
+    command: Command,
+    backend: Backend,
+    rng: ProtocolRng,
+    // a directory to load keys from, or save them to after setup
+    keys: Option<std::path::PathBuf>,
+    compression: Compression,
+    // public inputs to use instead of those of the example witness
+    statement: Option<Vec<Fr>>,
+}
 
-    let x = to_fq(3);
+// setup (or load the keys), prove, encode the artifacts and verify; any failure, including a
+// proof that does not verify or a circuit that is not satisfied, is an error
+fn run<C: ConstraintSynthesizer<Fr> + Clone>(options: &mut Options, circuit_id: &str, circuit: C, statement: Vec<Fr>) -> Result<(), Error> {
+    use arkworks_native_gadgets::from_field_elements;
+    use crate::encode;
 
-    let circuit = CubeDemo {
-        x: x,
-    };
+    let statement = options.statement.clone().unwrap_or(statement);
+    if options.command == Command::Stats {
+        print!("{}:\n{}", circuit_id, stats::report(circuit)?);
+        return Ok(());
+    }
+    if options.command == Command::Check {
+        return match check::check(circuit, &statement)? {
+            None => {
+                println!("{}: all constraints are satisfied for the statement", circuit_id);
+                Ok(())
+            }
+            Some(failure) => {
+                print!("{}: {}", circuit_id, failure);
+                Err("the circuit is not satisfied".into())
+            }
+        };
+    }
+
+    let backend = options.backend;
+    let rng = &mut options.rng;
 
-    let mut statement = Vec::new();
-    statement.push(to_fq(30));
-    let public_input = from_field_elements(&statement).unwrap();
+    let public_input = from_field_elements(&statement)?;
     println!("public_input: {:?}", public_input);
 
-    let param = generate_random_parameters::<Bls12_381, _, _>(circuit.clone(), &mut rng).unwrap();
-    let mut vkey_vec = Vec::new();
-    param.vk.serialize(&mut vkey_vec).unwrap();
+    let saved = match &options.keys {
+        Some(dir) => keys::load_keys(dir, circuit_id, backend)?,
+        None => None,
+    };
+    let keys = match saved {
+        Some(keys) => {
+            println!("... loaded {} keys for {}", backend, circuit_id);
+            keys
+        }
+        None => {
+            // one universal SRS of the configured maximum size, saved with the keys, so a new
+            // circuit is only indexed
+            let (srs, srs_seed) = match backend {
+                Backend::Groth16 | Backend::Gm17 => (None, None),
+                Backend::Marlin => {
+                    backend::check_index_size(circuit.clone())?;
+                    let (srs, seed) = keys::load_or_create_srs(options.keys.as_deref(), rng.seed(), rng)?;
+                    (Some(srs), seed)
+                }
+            };
+            let (pk, vk) = backend::setup(backend, circuit.clone(), srs.as_ref(), rng)?;
+            let pk = BoundProvingKey::bind(pk, circuit.clone())?;
+            let keys = keys::SavedKeys { pk, vk, insecure_seed: rng.seed().or(srs_seed) };
+            if let Some(dir) = &options.keys {
+                keys::save_keys(dir, circuit_id, &keys, options.compression)?;
+            }
+            keys
+        }
+    };
+    // a seeded setup stays insecure after its keys are saved, whatever rng proves with them
+    let warning = match keys.insecure_seed {
+        Some(seed) => Some(rng::insecure_seed_warning(seed)),
+        None => rng.warning(),
+    };
+    if let Some(warning) = &warning {
+        eprintln!("{}", warning);
+    }
+    let keys::SavedKeys { pk, vk, .. } = keys;
+    let vkey_vec = vk.to_bytes();
     println!("vkey_vec: {:?}", vkey_vec);
 
-    let proof = create_random_proof(circuit.clone(), &param, &mut rng).unwrap();
-    let mut proof_vec = Vec::new();
-    proof.serialize(&mut proof_vec).unwrap();
+    // saved keys only prove the circuit they were set up for
+    let proof = pk.prove(circuit, rng)?;
+    let proof_vec = proof.to_bytes();
     println!("proof_vec: {:?}", proof_vec);
 
+    encode::encode_parameters(std::path::Path::new("./file"), proof_vec, vkey_vec, public_input, warning)
+        .map_err(|e| format!("encoding the artifacts: {}", e))?;
 
-    let pvk = prepare_verifying_key(&param.vk);
+    let result = backend::verify(&vk, &statement, &proof, rng)?;
+    println!("verify result is {:?}", result);
+    if !result {
+        return Err("the proof does not verify".into());
+    }
+    Ok(())
+}
 
+// comma separated integers, negative ones mapped to the field by `to_fq`
+fn parse_list<T: std::str::FromStr>(flag: &str, arg: Option<String>) -> Result<Vec<T>, Error> {
+    let arg = arg.ok_or_else(|| format!("{} expects comma separated integers", flag))?;
+    arg.split(',')
+        .map(|value| value.trim().parse().map_err(|_| format!("{} expects comma separated integers, got {:?}", flag, value).into()))
+        .collect()
+}
 
-    encode::encode_parameters(proof_vec, vkey_vec, public_input);
+// usage: arkworks-example [cube | factor | hash | sha256 | matrix | hash-chain] [--backend groth16 | gm17 | marlin] [--insecure-seed <u64>]
+//                         [--keys <dir> [--uncompressed]] [--witness <x> | <p,q>] [--statement <out,...>]
+//                         [--size <message bytes> | <n,m,p> | <steps>]
+//        arkworks-example stats [cube | factor | hash | sha256 | matrix | hash-chain] [--size ...]
+//        arkworks-example check [cube | factor | hash | sha256 | matrix | hash-chain] [--witness ...] [--statement ...]
+//        arkworks-example ceremony ...
+//        arkworks-example bench [<steps> ...]
+// --witness replaces the example witness (x for cube, p,q for factor) and --statement the public
+// inputs, e.g. to see which constraint `check` reports for a wrong one. --size sets the shape of
+// the circuits built for a size, so `stats` reports their constraint counts. Exits with 1 on any error
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.first().map(|arg| arg.as_str()) {
+        Some("ceremony") => ceremony::command(&args[1..]),
+        Some("bench") => bench(&args[1..]),
+        _ => cli(args),
+    };
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
 
-    let result = verify_proof(&pvk, &proof, &statement).unwrap();
-    println!("verify result is {:?}", result);
+// how setup and proving time grow with the number of constraints, see `iterate::IteratedCubeDemo`
+fn bench(args: &[String]) -> Result<(), Error> {
+    let mut step_counts = vec![];
+    for arg in args {
+        step_counts.push(arg.parse().map_err(|_| format!("bench expects step counts, got {:?}", arg))?);
+    }
+    if step_counts.is_empty() {
+        step_counts = vec![1, 10, 100, 1000];
+    }
+    iterate::benchmark_iterated_cube(&step_counts)
+}
+
+fn cli(args: Vec<String>) -> Result<(), Error> {
+    let mut args = args.into_iter();
+    let mut circuit = None;
+    let mut witness = None;
+    let mut size = None;
+    let mut options = Options {
+        command: Command::Prove,
+        backend: Backend::Groth16,
+        // OS entropy unless a reproducible run is asked for
+        rng: ProtocolRng::os(),
+        keys: None,
+        compression: Compression::Compressed,
+        statement: None,
+    };
+    while let Some(arg) = args.next() {
+        if arg == "--backend" {
+            options.backend = args.next().as_deref().unwrap_or("").parse()?;
+        } else if arg == "--insecure-seed" {
+            match args.next().as_deref().unwrap_or("").parse() {
+                Ok(seed) => options.rng = ProtocolRng::insecure_seed(seed),
+                Err(_) => return Err("--insecure-seed expects an unsigned integer".into()),
+            }
+        } else if arg == "--keys" {
+            match args.next() {
+                Some(dir) => options.keys = Some(dir.into()),
+                None => return Err("--keys expects a directory".into()),
+            }
+        } else if arg == "--uncompressed" {
+            options.compression = Compression::Uncompressed;
+        } else if arg == "--witness" {
+            witness = Some(args.next());
+        } else if arg == "--size" {
+            size = Some(parse_list::<usize>("--size", args.next())?);
+        } else if arg == "--statement" {
+            let statement: Vec<i64> = parse_list("--statement", args.next())?;
+            options.statement = Some(statement.into_iter().map(to_fq).collect());
+        } else if arg == "stats" && circuit.is_none() && options.command == Command::Prove {
+            options.command = Command::Stats;
+        } else if arg == "check" && circuit.is_none() && options.command == Command::Prove {
+            options.command = Command::Check;
+        } else {
+            circuit = Some(arg);
+        }
+    }
+
+    let circuit_id = circuit.unwrap_or_else(|| "cube".to_string());
+    let result = match circuit_id.as_str() {
+        "cube" => {
+            // 3^3 + 3 == 30
+            let x = match witness {
+                Some(arg) => match parse_list::<i64>("--witness", arg)?[..] {
+                    [x] => to_fq(x),
+                    _ => return Err("cube: --witness expects x".into()),
+                },
+                None => to_fq(3),
+            };
+            run(&mut options, "cube", CubeDemo { x }, vec![x * x * x + x])
+        }
+        "factor" => {
+            // 3 * 11 == 33
+            let (p, q) = match witness {
+                Some(arg) => match parse_list::<u64>("--witness", arg)?[..] {
+                    [p, q] => (p, q),
+                    _ => return Err("factor: --witness expects p,q".into()),
+                },
+                None => (3, 11),
+            };
+            run(&mut options, "factor", FactorDemo { p, q }, vec![Fr::from(p as u128 * q as u128)])
+        }
+        "hash" => {
+            use ark_std::rand::{rngs::StdRng, SeedableRng};
+            use ark_crypto_primitives::crh::CRH;
+            use crate::hash::{HashDemo, TestCRH};
+
+            if witness.is_some() {
+                return Err("hash: the preimage is public, there is no --witness".into());
+            }
+            // the Pedersen generators are public and part of the circuit, so they are fixed
+            // for saved keys to stay valid; no secret comes from this seed
+            let params = TestCRH::setup(&mut StdRng::seed_from_u64(0u64))?;
+            let input = b"hello world".to_vec();
+            let image = TestCRH::evaluate(&params, &input)?;
+            let statement = hash::public_input(&input, &image);
+            run(&mut options, "hash", HashDemo { input, params, image }, statement)
+        }
+        "sha256" => {
+            use crate::sha256::Sha256Demo;
 
+            if witness.is_some() {
+                return Err("sha256: --witness is not supported, use --size for the message length".into());
+            }
+            let message = match size.as_deref() {
+                Some(&[len]) => (0..len).map(|i| i as u8).collect(),
+                Some(_) => return Err("sha256: --size expects the message length".into()),
+                None => b"hello arkworks".to_vec(),
+            };
+            let digest = sha256::sha256(&message);
+            run(&mut options, "sha256", Sha256Demo { message, digest }, sha256::public_input(&digest))
+        }
+        "matrix" => {
+            use crate::matrix::{Matrix, MatrixDemo};
+
+            if witness.is_some() {
+                return Err("matrix: --witness is not supported, use --size for the dimensions".into());
+            }
+            // A is n x m and B is m x p, filled with 1, 2, 3, ... row by row
+            let (n, m, p) = match size.as_deref() {
+                Some(&[n, m, p]) => (n, m, p),
+                Some(_) => return Err("matrix: --size expects n,m,p".into()),
+                None => (2, 2, 2),
+            };
+            let filled = |rows: usize, cols: usize| -> Matrix {
+                (0..rows).map(|i| (0..cols).map(|j| Fr::from((i * cols + j + 1) as u64)).collect()).collect()
+            };
+            let (a, b) = (filled(n, m), filled(m, p));
+            let c = matrix::matmul(&a, &b);
+            let statement = matrix::public_input(&c);
+            run(&mut options, "matrix", MatrixDemo { a, b, c }, statement)
+        }
+        "hash-chain" => {
+            use ark_std::rand::{rngs::StdRng, SeedableRng};
+            use ark_crypto_primitives::crh::CRH;
+            use crate::hash::TwoToOneHash;
+            use crate::hash_chain::HashChainDemo;
+
+            let seed = match witness {
+                Some(arg) => match parse_list::<i64>("--witness", arg)?[..] {
+                    [seed] => to_fq(seed),
+                    _ => return Err("hash-chain: --witness expects the seed".into()),
+                },
+                None => to_fq(1),
+            };
+            let num_steps = match size.as_deref() {
+                Some(&[num_steps]) => num_steps,
+                Some(_) => return Err("hash-chain: --size expects the number of steps".into()),
+                None => 8,
+            };
+            // public generators, fixed like those of `hash`
+            let params = <TwoToOneHash as CRH>::setup(&mut StdRng::seed_from_u64(0u64))?;
+            let output = hash_chain::hash_chain(&params, seed, num_steps)?;
+            run(&mut options, "hash-chain", HashChainDemo { params, seed, num_steps }, vec![output])
+        }
+        other => {
+            let expected = "cube, factor, hash, sha256, matrix, hash-chain";
+            return Err(format!("unknown circuit {:?}, expected one of: {}", other, expected).into());
+        }
+    };
+    result.map_err(|e| format!("{}: {}", circuit_id, e).into())
+}
+
+#[test]
+fn test_factor_proof() {
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use ark_bls12_381::Bls12_381;
+    use ark_groth16::*;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    // two 32-bit primes
+    let (p, q) = (4294967291u64, 4294967279u64);
+    let n = Fr::from(p as u128 * q as u128);
+    let circuit = FactorDemo { p, q };
+
+    let param = generate_random_parameters::<Bls12_381, _, _>(circuit.clone(), &mut rng).unwrap();
+    let proof = create_random_proof(circuit, &param, &mut rng).unwrap();
+    let pvk = prepare_verifying_key(&param.vk);
+
+    assert!(verify_proof(&pvk, &proof, &[n]).unwrap());
+    assert!(!verify_proof(&pvk, &proof, &[n + to_fq(2)]).unwrap());
+
+    // trivial factorizations are rejected
+    for (p, q) in [(1, 33), (33, 1), (0, 0)] {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        FactorDemo { p, q }.generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
 }
@@ -0,0 +1,145 @@
+use ark_bls12_381::Fr;
+use ark_ff::Zero;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+
+// row-major matrix
+pub type Matrix = Vec<Vec<Fr>>;
+
+// native reference: C = A * B
+pub fn matmul(a: &Matrix, b: &Matrix) -> Matrix {
+    let inner = b.len();
+    let cols = b.first().map_or(0, |row| row.len());
+    a.iter()
+        .map(|row| {
+            assert_eq!(row.len(), inner);
+            (0..cols)
+                .map(|j| (0..inner).fold(Fr::zero(), |acc, k| acc + row[k] * b[k][j]))
+                .collect()
+        })
+        .collect()
+}
+
+// proving that I know A (n x m) and B (m x p) with A * B equal to the public C (n x p)
+#[derive(Clone)]
+pub struct MatrixDemo {
+    pub a: Matrix,
+    pub b: Matrix,
+    pub c: Matrix,
+}
+
+impl MatrixDemo {
+    // a circuit of the given shape, for setup
+    #[cfg(test)]
+    pub fn blank(n: usize, m: usize, p: usize) -> Self {
+        MatrixDemo {
+            a: vec![vec![Fr::zero(); m]; n],
+            b: vec![vec![Fr::zero(); p]; m],
+            c: vec![vec![Fr::zero(); p]; n],
+        }
+    }
+}
+
+impl ConstraintSynthesizer<Fr> for MatrixDemo {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+
+        let mut c = vec![];
+        for row in self.c.iter() {
+            c.push(Vec::<FpVar<Fr>>::new_input(ark_relations::ns!(cs, "new input c"), || Ok(row.clone()))?);
+        }
+
+        let mut a = vec![];
+        for row in self.a.iter() {
+            a.push(Vec::<FpVar<Fr>>::new_witness(ark_relations::ns!(cs, "new witness a"), || Ok(row.clone()))?);
+        }
+        let mut b = vec![];
+        for row in self.b.iter() {
+            b.push(Vec::<FpVar<Fr>>::new_witness(ark_relations::ns!(cs, "new witness b"), || Ok(row.clone()))?);
+        }
+
+        for (i, c_row) in c.iter().enumerate() {
+            for (j, c_ij) in c_row.iter().enumerate() {
+                let mut sum = FpVar::<Fr>::zero();
+                for k in 0..b.len() {
+                    // every product is a new witness, the sum is free
+                    let product = FpVar::<Fr>::new_witness(
+                        ark_relations::ns!(cs, "new witness a_ik * b_kj"),
+                        || Ok(self.a[i][k] * self.b[k][j])
+                    )?;
+                    a[i][k].mul_equals(&b[k][j], &product)?;
+                    sum += product;
+                }
+                sum.enforce_equal(c_ij)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// the verifier statement: C in row-major order
+pub fn public_input(c: &Matrix) -> Vec<Fr> {
+    c.iter().flatten().cloned().collect()
+}
+
+// number of constraints for A (n x m) * B (m x p): n * m * p products and n * p sums, see also
+// `stats matrix --size n,m,p`
+#[cfg(test)]
+pub fn num_constraints(n: usize, m: usize, p: usize) -> usize {
+    use ark_relations::r1cs::{ConstraintSystem, SynthesisMode};
+
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    cs.set_mode(SynthesisMode::Setup);
+    MatrixDemo::blank(n, m, p).generate_constraints(cs.clone()).unwrap();
+    cs.num_constraints()
+}
+
+#[test]
+fn test_matrix_proof() {
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use ark_std::UniformRand;
+    use ark_bls12_381::Bls12_381;
+    use ark_groth16::*;
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let (n, m, p) = (3, 4, 2);
+    let a: Matrix = (0..n).map(|_| (0..m).map(|_| Fr::rand(&mut rng)).collect()).collect();
+    let b: Matrix = (0..m).map(|_| (0..p).map(|_| Fr::rand(&mut rng)).collect()).collect();
+    let c = matmul(&a, &b);
+
+    let param = generate_random_parameters::<Bls12_381, _, _>(MatrixDemo::blank(n, m, p), &mut rng).unwrap();
+    let proof = create_random_proof(MatrixDemo { a, b, c: c.clone() }, &param, &mut rng).unwrap();
+    let pvk = prepare_verifying_key(&param.vk);
+
+    assert!(verify_proof(&pvk, &proof, &public_input(&c)).unwrap());
+
+    let mut wrong = c;
+    wrong[2][1] += Fr::from(1u64);
+    assert!(!verify_proof(&pvk, &proof, &public_input(&wrong)).unwrap());
+}
+
+#[test]
+fn test_matrix_constraint_counts() {
+    let x: Matrix = vec![
+        vec![Fr::from(1u64), Fr::from(2u64)],
+        vec![Fr::from(3u64), Fr::from(4u64)],
+    ];
+    let y: Matrix = vec![
+        vec![Fr::from(5u64), Fr::from(6u64)],
+        vec![Fr::from(7u64), Fr::from(8u64)],
+    ];
+    let expected: Matrix = vec![
+        vec![Fr::from(19u64), Fr::from(22u64)],
+        vec![Fr::from(43u64), Fr::from(50u64)],
+    ];
+    assert_eq!(matmul(&x, &y), expected);
+
+    for dim in [1, 2, 4, 8, 16] {
+        let count = num_constraints(dim, dim, dim);
+        assert_eq!(count, dim * dim * dim + dim * dim);
+        println!("{}x{} * {}x{}: {} constraints", dim, dim, dim, dim, count);
+    }
+    assert_eq!(num_constraints(2, 3, 4), 2 * 3 * 4 + 2 * 4);
+}
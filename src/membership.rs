@@ -0,0 +1,143 @@
+use ark_bls12_381::Fr;
+use ark_crypto_primitives::crh::{pedersen, CRHGadget, CRH};
+use ark_crypto_primitives::Error;
+use ark_ed_on_bls12_381::EdwardsProjective as JubJub;
+use ark_ff::{to_bytes, One, Zero};
+use ark_r1cs_std::prelude::*;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+
+use crate::hash::{TestCRH, TestCRHGadget};
+
+// native reference: Z_S(x) = (x - s_0)(x - s_1)...(x - s_n-1), zero iff x is in S
+pub fn vanishing_eval(set: &[Fr], x: Fr) -> Fr {
+    set.iter().fold(Fr::one(), |acc, s| acc * (x - s))
+}
+
+// native reference: h_0 = 0, h_i+1 = H(h_i || s_i).x over the `hash.rs` Pedersen window,
+// which is exactly two 32-byte field elements wide
+pub fn set_digest(params: &pedersen::Parameters<JubJub>, set: &[Fr]) -> Result<Fr, Error> {
+    let mut digest = Fr::zero();
+    for s in set {
+        digest = TestCRH::evaluate(params, &to_bytes![digest, s]?)?.x;
+    }
+    Ok(digest)
+}
+
+// proving that I know x in the set S, where S is either public
+// or hidden behind the public digest `set_digest(params, S)`
+#[derive(Clone)]
+pub struct MembershipDemo {
+    pub x: Fr,
+    pub set: Vec<Fr>,
+    pub digest_params: Option<pedersen::Parameters<JubJub>>,
+}
+
+impl ConstraintSynthesizer<Fr> for MembershipDemo {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+
+        let set = match self.digest_params {
+            None => Vec::<FpVar<Fr>>::new_input(ark_relations::ns!(cs, "set"), || Ok(self.set.clone()))?,
+            Some(ref params) => {
+                let digest = FpVar::<Fr>::new_input(
+                    ark_relations::ns!(cs, "set digest"),
+                    || set_digest(params, &self.set).map_err(|_| SynthesisError::AssignmentMissing)
+                )?;
+                let set = Vec::<FpVar<Fr>>::new_witness(ark_relations::ns!(cs, "set"), || Ok(self.set.clone()))?;
+
+                let params = pedersen::constraints::CRHParametersVar::new_constant(
+                    ark_relations::ns!(cs, "parameters"), params
+                )?;
+                let mut result = FpVar::<Fr>::zero();
+                for s in set.iter() {
+                    let mut input = result.to_bytes()?;
+                    input.extend(s.to_bytes()?);
+                    result = TestCRHGadget::evaluate(&params, &input)?.x;
+                }
+                result.enforce_equal(&digest)?;
+
+                set
+            }
+        };
+
+        // x is the witness which should be hidden
+        let x_witness = FpVar::<Fr>::new_witness(
+            ark_relations::ns!(cs, "new witness x"), || Ok(self.x)
+        )?;
+
+        // multiplication chain acc_i = acc_i-1 * (x - s_i), one constraint per set element
+        let mut acc = FpVar::<Fr>::one();
+        for s in set.iter() {
+            let factor = &x_witness - s;
+            let product = FpVar::<Fr>::new_witness(
+                ark_relations::ns!(cs, "new witness product"), || Ok(acc.value()? * factor.value()?)
+            )?;
+            acc.mul_equals(&factor, &product)?;
+            acc = product;
+        }
+
+        acc.enforce_equal(&FpVar::zero())?;
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_membership_public_set() {
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use ark_bls12_381::Bls12_381;
+    use ark_groth16::*;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let set: Vec<Fr> = [3u64, 30, 35, 42].iter().map(|&s| Fr::from(s)).collect();
+    assert!(vanishing_eval(&set, Fr::from(35u64)).is_zero());
+    assert!(!vanishing_eval(&set, Fr::from(36u64)).is_zero());
+
+    let circuit = MembershipDemo { x: Fr::from(35u64), set: set.clone(), digest_params: None };
+
+    let param = generate_random_parameters::<Bls12_381, _, _>(circuit.clone(), &mut rng).unwrap();
+    let proof = create_random_proof(circuit, &param, &mut rng).unwrap();
+    let pvk = prepare_verifying_key(&param.vk);
+
+    assert!(verify_proof(&pvk, &proof, &set).unwrap());
+
+    let circuit = MembershipDemo { x: Fr::from(36u64), set, digest_params: None };
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    circuit.generate_constraints(cs.clone()).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn test_membership_committed_set() {
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use ark_bls12_381::Bls12_381;
+    use ark_groth16::*;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let params = TestCRH::setup(&mut rng).unwrap();
+    let set: Vec<Fr> = [3u64, 30, 35, 42].iter().map(|&s| Fr::from(s)).collect();
+    let digest = set_digest(&params, &set).unwrap();
+
+    let circuit = MembershipDemo { x: Fr::from(3u64), set: set.clone(), digest_params: Some(params.clone()) };
+
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    circuit.clone().generate_constraints(cs.clone()).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+
+    let param = generate_random_parameters::<Bls12_381, _, _>(circuit.clone(), &mut rng).unwrap();
+    let proof = create_random_proof(circuit, &param, &mut rng).unwrap();
+    let pvk = prepare_verifying_key(&param.vk);
+
+    assert!(verify_proof(&pvk, &proof, &[digest]).unwrap());
+
+    // a proof for another set does not verify against the original digest
+    let mut other = set;
+    other[0] = Fr::from(4u64);
+    let circuit = MembershipDemo { x: Fr::from(4u64), set: other, digest_params: Some(params) };
+    let proof = create_random_proof(circuit, &param, &mut rng).unwrap();
+    assert!(!verify_proof(&pvk, &proof, &[digest]).unwrap());
+}
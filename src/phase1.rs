@@ -0,0 +1,586 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+use ark_bls12_381::{Bls12_381, Fq, Fq2, FqParameters, Fr, G1Affine, G1Projective, G2Affine, G2Projective};
+use ark_crypto_primitives::Error;
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{BigInteger384, FpParameters, FromBytes, PrimeField, UniformRand, Zero};
+use ark_groth16::{ProvingKey, VerifyingKey};
+use ark_poly::{domain::DomainCoeff, EvaluationDomain, Radix2EvaluationDomain};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem, OptimizationGoal, SynthesisMode};
+use ark_std::rand::RngCore;
+
+use crate::ceremony::{combine, same_ratio};
+
+// the output of a phase-1 powers-of-tau ceremony for 2^power constraints:
+// tau^i G1 for i < 2n - 1, tau^i G2, alpha tau^i G1 and beta tau^i G1 for i < n, and beta G2
+#[derive(Clone, Debug, PartialEq)]
+pub struct Phase1 {
+    pub tau_g1: Vec<G1Affine>,
+    pub tau_g2: Vec<G2Affine>,
+    pub alpha_tau_g1: Vec<G1Affine>,
+    pub beta_tau_g1: Vec<G1Affine>,
+    pub beta_g2: G2Affine,
+}
+
+fn check_g1(point: G1Affine) -> Result<G1Affine, Error> {
+    if point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve() {
+        Ok(point)
+    } else {
+        Err("G1 point is not in the prime order subgroup".into())
+    }
+}
+
+fn check_g2(point: G2Affine) -> Result<G2Affine, Error> {
+    if point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve() {
+        Ok(point)
+    } else {
+        Err("G2 point is not in the prime order subgroup".into())
+    }
+}
+
+// snarkjs stores base field elements little-endian in Montgomery form, points at infinity as zeros
+fn read_fq_montgomery<R: Read>(reader: &mut R) -> Result<Fq, Error> {
+    let repr = BigInteger384::read(reader)?;
+    if repr >= FqParameters::MODULUS {
+        return Err("field element out of range".into());
+    }
+    Ok(Fq::new(repr))
+}
+
+fn read_ptau_g1<R: Read>(reader: &mut R) -> Result<G1Affine, Error> {
+    let (x, y) = (read_fq_montgomery(reader)?, read_fq_montgomery(reader)?);
+    if x.is_zero() && y.is_zero() {
+        return Ok(G1Affine::zero());
+    }
+    check_g1(G1Affine::new(x, y, false))
+}
+
+fn read_ptau_g2<R: Read>(reader: &mut R) -> Result<G2Affine, Error> {
+    let x = Fq2::new(read_fq_montgomery(reader)?, read_fq_montgomery(reader)?);
+    let y = Fq2::new(read_fq_montgomery(reader)?, read_fq_montgomery(reader)?);
+    if x.is_zero() && y.is_zero() {
+        return Ok(G2Affine::zero());
+    }
+    check_g2(G2Affine::new(x, y, false))
+}
+
+// the zcash powersoftau files store base field elements big-endian, with flags in the top bits
+// of the first byte: 0x80 compressed, 0x40 infinity, 0x20 the larger of y and -y
+const COMPRESSED: u8 = 0x80;
+const INFINITY: u8 = 0x40;
+const GREATEST: u8 = 0x20;
+
+fn fq_from_be(bytes: &[u8]) -> Result<Fq, Error> {
+    let mut le = bytes.to_vec();
+    le.reverse();
+    Fq::from_repr(BigInteger384::read(&le[..])?).ok_or_else(|| "field element out of range".into())
+}
+
+// reads a point encoding, returning its flags and the coordinate bytes with the flags cleared
+fn read_flagged<R: Read>(reader: &mut R, len: usize, compressed: bool) -> Result<Option<(u8, Vec<u8>)>, Error> {
+    let mut bytes = vec![0u8; len];
+    reader.read_exact(&mut bytes)?;
+    let flags = bytes[0] & 0xe0;
+    bytes[0] &= 0x1f;
+
+    if (flags & COMPRESSED != 0) != compressed {
+        return Err("unexpected point compression".into());
+    }
+    if flags & INFINITY != 0 {
+        if bytes.iter().any(|&b| b != 0) {
+            return Err("invalid encoding of the point at infinity".into());
+        }
+        return Ok(None);
+    }
+    Ok(Some((flags, bytes)))
+}
+
+fn read_powersoftau_g1<R: Read>(reader: &mut R, compressed: bool) -> Result<G1Affine, Error> {
+    let (flags, bytes) = match read_flagged(reader, if compressed { 48 } else { 96 }, compressed)? {
+        Some(encoding) => encoding,
+        None => return Ok(G1Affine::zero()),
+    };
+    let x = fq_from_be(&bytes[..48])?;
+    let point = if compressed {
+        G1Affine::get_point_from_x(x, flags & GREATEST != 0).ok_or("G1 point is not on the curve")?
+    } else {
+        G1Affine::new(x, fq_from_be(&bytes[48..])?, false)
+    };
+    check_g1(point)
+}
+
+fn read_powersoftau_g2<R: Read>(reader: &mut R, compressed: bool) -> Result<G2Affine, Error> {
+    let (flags, bytes) = match read_flagged(reader, if compressed { 96 } else { 192 }, compressed)? {
+        Some(encoding) => encoding,
+        None => return Ok(G2Affine::zero()),
+    };
+    let x = Fq2::new(fq_from_be(&bytes[48..96])?, fq_from_be(&bytes[..48])?);
+    let point = if compressed {
+        G2Affine::get_point_from_x(x, flags & GREATEST != 0).ok_or("G2 point is not on the curve")?
+    } else {
+        G2Affine::new(x, Fq2::new(fq_from_be(&bytes[144..])?, fq_from_be(&bytes[96..144])?), false)
+    };
+    check_g2(point)
+}
+
+fn read_points<R: Read, P>(
+    reader: &mut R,
+    count: usize,
+    read: impl Fn(&mut R) -> Result<P, Error>,
+) -> Result<Vec<P>, Error> {
+    (0..count).map(|_| read(reader)).collect()
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> Result<u32, Error> {
+    Ok(u32::read(reader)?)
+}
+
+// header of a zcash challenge or response file: the hash of the previous file
+const POWERSOFTAU_HASH_SIZE: usize = 64;
+
+// ptau files go up to 2^28 powers, more than any circuit here needs
+const MAX_POWER: u32 = 28;
+
+// public key of the last participant at the end of a response file: 6 uncompressed G1 and 3 uncompressed G2 points
+const POWERSOFTAU_PUBLIC_KEY_SIZE: usize = 6 * 96 + 3 * 192;
+
+impl Phase1 {
+    // number of constraints plus public inputs these powers can set up
+    pub fn size(&self) -> usize {
+        self.tau_g2.len()
+    }
+
+    // a snarkjs .ptau file over BLS12-381
+    pub fn read_ptau<R: Read>(mut reader: R) -> Result<Self, Error> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != b"ptau" {
+            return Err("not a ptau file".into());
+        }
+        let _version = read_u32(&mut reader)?;
+
+        let mut sections = HashMap::new();
+        for _ in 0..read_u32(&mut reader)? {
+            let id = read_u32(&mut reader)?;
+            let size = u64::read(&mut reader)?;
+            // read what is there rather than allocating a size taken from the file
+            let mut data = vec![];
+            (&mut reader).take(size).read_to_end(&mut data)?;
+            if data.len() as u64 != size {
+                return Err(format!("ptau section {} is truncated", id).into());
+            }
+            sections.insert(id, data);
+        }
+        let section = |id: u32| sections.get(&id).map(|data| &data[..]).ok_or(format!("ptau section {} is missing", id));
+
+        let mut header = section(1)?;
+        let n8 = read_u32(&mut header)? as usize;
+        if n8 != 48 {
+            return Err("ptau file is not over BLS12-381".into());
+        }
+        let mut modulus = vec![0u8; n8];
+        header.read_exact(&mut modulus)?;
+        let mut expected = vec![];
+        ark_ff::ToBytes::write(&FqParameters::MODULUS, &mut expected)?;
+        if modulus != expected {
+            return Err("ptau file is not over BLS12-381".into());
+        }
+        let power = read_u32(&mut header)?;
+        if power > MAX_POWER {
+            return Err(format!("ptau file claims 2^{} powers, at most 2^{} are supported", power, MAX_POWER).into());
+        }
+        let n = 1usize << power;
+
+        Ok(Phase1 {
+            tau_g1: read_points(&mut section(2)?, 2 * n - 1, read_ptau_g1)?,
+            tau_g2: read_points(&mut section(3)?, n, read_ptau_g2)?,
+            alpha_tau_g1: read_points(&mut section(4)?, n, read_ptau_g1)?,
+            beta_tau_g1: read_points(&mut section(5)?, n, read_ptau_g1)?,
+            beta_g2: read_ptau_g2(&mut section(6)?)?,
+        })
+    }
+
+    // a zcash powersoftau challenge (uncompressed) or response (compressed) file for n = 2^power
+    pub fn read_powersoftau<R: Read>(mut reader: R, n: usize, compressed: bool) -> Result<Self, Error> {
+        if n == 0 || n > 1 << MAX_POWER {
+            return Err(format!("{} powers of tau are not supported", n).into());
+        }
+        let mut hash = [0u8; POWERSOFTAU_HASH_SIZE];
+        reader.read_exact(&mut hash)?;
+
+        let g1 = |reader: &mut R| read_powersoftau_g1(reader, compressed);
+        let g2 = |reader: &mut R| read_powersoftau_g2(reader, compressed);
+        Ok(Phase1 {
+            tau_g1: read_points(&mut reader, 2 * n - 1, g1)?,
+            tau_g2: read_points(&mut reader, n, g2)?,
+            alpha_tau_g1: read_points(&mut reader, n, g1)?,
+            beta_tau_g1: read_points(&mut reader, n, g1)?,
+            beta_g2: g2(&mut reader)?,
+        })
+    }
+
+    // checks that the points are the powers of one tau, scaled by one alpha and one beta,
+    // with random linear combinations so that each sequence costs a single pairing check.
+    // Parsing only checks that each point is in its group.
+    pub fn verify<R: RngCore>(&self, rng: &mut R) -> Result<(), Error> {
+        let n = self.size();
+        if n < 2 || self.tau_g1.len() != 2 * n - 1 || self.alpha_tau_g1.len() != n || self.beta_tau_g1.len() != n {
+            return Err("powers of tau have inconsistent lengths".into());
+        }
+        let (g1, g2) = (self.tau_g1[0], self.tau_g2[0]);
+        if g1 != G1Affine::prime_subgroup_generator() || g2 != G2Affine::prime_subgroup_generator() {
+            return Err("powers of tau do not start at the generators".into());
+        }
+        if self.tau_g1[1].is_zero() || self.alpha_tau_g1[0].is_zero() || self.beta_tau_g1[0].is_zero() {
+            return Err("tau, alpha or beta is zero".into());
+        }
+
+        // each point is tau times the previous one
+        let tau_g2 = self.tau_g2[1];
+        for (name, points) in [("tau", &self.tau_g1), ("alpha tau", &self.alpha_tau_g1), ("beta tau", &self.beta_tau_g1)] {
+            let coefficients: Vec<Fr> = (1..points.len()).map(|_| Fr::rand(rng)).collect();
+            let (before, after) = (&points[..points.len() - 1], &points[1..]);
+            if !same_ratio(combine(after, &coefficients), g2, combine(before, &coefficients), tau_g2) {
+                return Err(format!("{} powers in G1 are not consecutive", name).into());
+            }
+        }
+        let coefficients: Vec<Fr> = (1..n).map(|_| Fr::rand(rng)).collect();
+        let combine_g2 = |points: &[G2Affine]| -> G2Affine {
+            points.iter().zip(&coefficients).map(|(p, c)| p.mul(c.into_repr())).sum::<G2Projective>().into_affine()
+        };
+        if !same_ratio(g1, combine_g2(&self.tau_g2[1..]), self.tau_g1[1], combine_g2(&self.tau_g2[..n - 1])) {
+            return Err("tau powers in G2 are not consecutive".into());
+        }
+
+        if !same_ratio(self.beta_tau_g1[0], g2, g1, self.beta_g2) {
+            return Err("beta in G1 and G2 differ".into());
+        }
+        Ok(())
+    }
+
+    // a ptau file, or a challenge or response file whose size gives the number of powers,
+    // checked for consistency
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let phase1 = Self::load_unchecked(path.as_ref())?;
+        phase1.verify(&mut ark_std::rand::rngs::OsRng)?;
+        Ok(phase1)
+    }
+
+    fn load_unchecked(path: &Path) -> Result<Self, Error> {
+        let mut file = BufReader::new(File::open(path)?);
+        let mut magic = [0u8; 4];
+        file.read_exact(&mut magic)?;
+        let file = BufReader::new(File::open(path)?);
+        if &magic == b"ptau" {
+            return Self::read_ptau(file);
+        }
+
+        // 576n + 96 bytes of uncompressed points, 288n + 48 of compressed ones
+        let size = std::fs::metadata(path)?.len() as usize;
+        let points = size.saturating_sub(POWERSOFTAU_HASH_SIZE);
+        if points >= 96 && (points - 96).is_multiple_of(576) && ((points - 96) / 576).is_power_of_two() {
+            return Self::read_powersoftau(file, (points - 96) / 576, false);
+        }
+        let points = points.saturating_sub(POWERSOFTAU_PUBLIC_KEY_SIZE);
+        if points >= 48 && (points - 48).is_multiple_of(288) && ((points - 48) / 288).is_power_of_two() {
+            return Self::read_powersoftau(file, (points - 48) / 288, true);
+        }
+        Err("unrecognized powers of tau file".into())
+    }
+
+    // Groth16 parameters for the circuit with gamma = delta = 1, laid out as `generate_random_parameters`
+    // lays them out: the QAP domain covers the constraints and one copy constraint per public input.
+    // With gamma = delta proofs are malleable across public inputs, so these parameters are only a
+    // starting point for a phase-2 ceremony (`ceremony init --ptau`), and `backend` refuses to prove
+    // or verify with them as they are. `ceremony export` saves the result as keys for `--keys`.
+    pub fn proving_key<C: ConstraintSynthesizer<Fr>>(&self, circuit: C) -> Result<ProvingKey<Bls12_381>, Error> {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        cs.set_optimization_goal(OptimizationGoal::Constraints);
+        cs.set_mode(SynthesisMode::Setup);
+        circuit.generate_constraints(cs.clone())?;
+        cs.finalize();
+        let matrices = cs.to_matrices().ok_or("constraint system has no matrices")?;
+
+        let num_constraints = cs.num_constraints();
+        let num_inputs = cs.num_instance_variables();
+        let num_variables = num_inputs + cs.num_witness_variables();
+
+        let domain = Radix2EvaluationDomain::<Fr>::new(num_constraints + num_inputs)
+            .ok_or("circuit is too large for the field")?;
+        let m = domain.size();
+        if self.size() < m {
+            return Err(format!("the powers of tau cover {} constraints, the circuit needs {}", self.size(), m).into());
+        }
+
+        // L_k(tau), alpha L_k(tau), beta L_k(tau) for the Lagrange basis of the domain
+        let l_g1 = lagrange::<G1Projective>(&domain, &self.tau_g1[..m]);
+        let l_g2 = lagrange::<G2Projective>(&domain, &self.tau_g2[..m]);
+        let alpha_l_g1 = lagrange::<G1Projective>(&domain, &self.alpha_tau_g1[..m]);
+        let beta_l_g1 = lagrange::<G1Projective>(&domain, &self.beta_tau_g1[..m]);
+
+        let mut a_g1 = vec![G1Projective::zero(); num_variables];
+        let mut b_g1 = vec![G1Projective::zero(); num_variables];
+        let mut b_g2 = vec![G2Projective::zero(); num_variables];
+        // beta a_i(tau) + alpha b_i(tau) + c_i(tau)
+        let mut abc_g1 = vec![G1Projective::zero(); num_variables];
+
+        for (k, row) in matrices.a.iter().enumerate() {
+            for &(coeff, i) in row {
+                a_g1[i] += l_g1[k].mul(coeff.into_repr());
+                abc_g1[i] += beta_l_g1[k].mul(coeff.into_repr());
+            }
+        }
+        for i in 0..num_inputs {
+            a_g1[i] += l_g1[num_constraints + i];
+            abc_g1[i] += beta_l_g1[num_constraints + i];
+        }
+        for (k, row) in matrices.b.iter().enumerate() {
+            for &(coeff, i) in row {
+                b_g1[i] += l_g1[k].mul(coeff.into_repr());
+                b_g2[i] += l_g2[k].mul(coeff.into_repr());
+                abc_g1[i] += alpha_l_g1[k].mul(coeff.into_repr());
+            }
+        }
+        for (k, row) in matrices.c.iter().enumerate() {
+            for &(coeff, i) in row {
+                abc_g1[i] += l_g1[k].mul(coeff.into_repr());
+            }
+        }
+
+        // tau^i Z(tau) = tau^(i + m) - tau^i
+        let h_g1: Vec<G1Projective> = (0..m - 1)
+            .map(|i| self.tau_g1[i + m].into_projective() - self.tau_g1[i].into_projective())
+            .collect();
+
+        let abc_g1 = G1Projective::batch_normalization_into_affine(&abc_g1);
+        let vk = VerifyingKey {
+            alpha_g1: self.alpha_tau_g1[0],
+            beta_g2: self.beta_g2,
+            gamma_g2: self.tau_g2[0],
+            delta_g2: self.tau_g2[0],
+            gamma_abc_g1: abc_g1[..num_inputs].to_vec(),
+        };
+        Ok(ProvingKey {
+            vk,
+            beta_g1: self.beta_tau_g1[0],
+            delta_g1: self.tau_g1[0],
+            a_query: G1Projective::batch_normalization_into_affine(&a_g1),
+            b_g1_query: G1Projective::batch_normalization_into_affine(&b_g1),
+            b_g2_query: G2Projective::batch_normalization_into_affine(&b_g2),
+            h_query: G1Projective::batch_normalization_into_affine(&h_g1),
+            l_query: abc_g1[num_inputs..].to_vec(),
+        })
+    }
+}
+
+// the inverse FFT of tau^0, ..., tau^(m-1) in the exponent gives L_0(tau), ..., L_(m-1)(tau)
+fn lagrange<G: ProjectiveCurve<ScalarField = Fr> + DomainCoeff<Fr>>(
+    domain: &Radix2EvaluationDomain<Fr>,
+    powers: &[G::Affine],
+) -> Vec<G> {
+    let mut points: Vec<G> = powers.iter().map(|p| p.into_projective()).collect();
+    domain.ifft_in_place(&mut points);
+    points
+}
+
+// phase-1 output for known secrets, as a single participant would produce it
+#[cfg(test)]
+pub(crate) fn phase1_from_secrets(n: usize, tau: Fr, alpha: Fr, beta: Fr) -> Phase1 {
+    use ark_ff::Field;
+
+    let g1 = G1Affine::prime_subgroup_generator();
+    let g2 = G2Affine::prime_subgroup_generator();
+    let powers: Vec<Fr> = (0..2 * n - 1).map(|i| tau.pow([i as u64])).collect();
+    Phase1 {
+        tau_g1: powers.iter().map(|p| g1.mul(p.into_repr()).into_affine()).collect(),
+        tau_g2: powers[..n].iter().map(|p| g2.mul(p.into_repr()).into_affine()).collect(),
+        alpha_tau_g1: powers[..n].iter().map(|p| g1.mul((alpha * p).into_repr()).into_affine()).collect(),
+        beta_tau_g1: powers[..n].iter().map(|p| g1.mul((beta * p).into_repr()).into_affine()).collect(),
+        beta_g2: g2.mul(beta.into_repr()).into_affine(),
+    }
+}
+
+#[test]
+fn test_ptau_import() {
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use ark_std::UniformRand;
+    use ark_ff::ToBytes;
+    use crate::backend;
+    use crate::keys::{self, Compression};
+    use crate::{to_fq, CubeDemo};
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let power = 3u32;
+    let phase1 = phase1_from_secrets(1 << power, Fr::rand(&mut rng), Fr::rand(&mut rng), Fr::rand(&mut rng));
+
+    // snarkjs layout: Montgomery form little-endian, x before y, c0 before c1
+    let write_g1 = |out: &mut Vec<u8>, p: &G1Affine| {
+        p.x.0.write(&mut *out).unwrap();
+        p.y.0.write(&mut *out).unwrap();
+    };
+    let write_g2 = |out: &mut Vec<u8>, p: &G2Affine| {
+        for c in [p.x.c0, p.x.c1, p.y.c0, p.y.c1] {
+            c.0.write(&mut *out).unwrap();
+        }
+    };
+    let mut header = vec![];
+    48u32.write(&mut header).unwrap();
+    FqParameters::MODULUS.write(&mut header).unwrap();
+    power.write(&mut header).unwrap();
+    power.write(&mut header).unwrap();
+    let mut sections = vec![(1u32, header), (2, vec![]), (3, vec![]), (4, vec![]), (5, vec![]), (6, vec![])];
+    phase1.tau_g1.iter().for_each(|p| write_g1(&mut sections[1].1, p));
+    phase1.tau_g2.iter().for_each(|p| write_g2(&mut sections[2].1, p));
+    phase1.alpha_tau_g1.iter().for_each(|p| write_g1(&mut sections[3].1, p));
+    phase1.beta_tau_g1.iter().for_each(|p| write_g1(&mut sections[4].1, p));
+    write_g2(&mut sections[5].1, &phase1.beta_g2);
+
+    let build = |sections: &[(u32, Vec<u8>)]| {
+        let mut ptau = b"ptau".to_vec();
+        1u32.write(&mut ptau).unwrap();
+        (sections.len() as u32).write(&mut ptau).unwrap();
+        // sections may come in any order
+        for (id, data) in sections.iter().rev() {
+            id.write(&mut ptau).unwrap();
+            (data.len() as u64).write(&mut ptau).unwrap();
+            ptau.extend(data);
+        }
+        ptau
+    };
+    let ptau = build(&sections);
+
+    let path = std::env::temp_dir().join("arkworks-example-phase1-test.ptau");
+    std::fs::write(&path, &ptau).unwrap();
+    let loaded = Phase1::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded, phase1);
+
+    // a key derived from the powers of tau has delta == gamma and is refused as it is
+    let pk = loaded.proving_key(CubeDemo { x: Fr::zero() }).unwrap();
+    assert!(backend::prove(&backend::ProvingKey::Groth16(pk.clone()), CubeDemo { x: to_fq(3) }, &mut rng).is_err());
+    let mut phase2 = crate::ceremony::Phase2::from_params("cube", pk);
+    assert!(phase2.verify(&mut rng).is_err());
+
+    // after a phase-2 contribution it is exported as the key pair `run --keys` loads, which proves and verifies
+    phase2.contribute(&mut rng).unwrap();
+    let dir = std::env::temp_dir().join("arkworks-example-phase1-keys-test");
+    phase2.export(&dir, CubeDemo { x: Fr::zero() }, Compression::Compressed, &mut rng).unwrap();
+    let keys::SavedKeys { pk, vk, .. } = keys::load_keys(&dir, "cube", backend::Backend::Groth16).unwrap().unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    let proof = pk.prove(CubeDemo { x: to_fq(3) }, &mut rng).unwrap();
+    assert!(backend::verify(&vk, &[to_fq(30)], &proof, &mut rng).unwrap());
+    assert!(!backend::verify(&vk, &[to_fq(31)], &proof, &mut rng).unwrap());
+
+    // too few powers for the circuit
+    let small = phase1_from_secrets(2, Fr::rand(&mut rng), Fr::rand(&mut rng), Fr::rand(&mut rng));
+    assert!(small.proving_key(CubeDemo { x: Fr::zero() }).is_err());
+
+    // a corrupted point is rejected: section 6 comes first, after the 12-byte file and section headers
+    let mut corrupted = ptau.clone();
+    corrupted[24 + 5] ^= 1;
+    assert!(Phase1::read_ptau(&corrupted[..]).is_err());
+
+    // valid points that are not consecutive powers parse but do not verify
+    let mut inconsistent = phase1.clone();
+    inconsistent.tau_g1[2] = inconsistent.tau_g1[3];
+    assert!(inconsistent.verify(&mut rng).is_err());
+    let mut inconsistent = phase1.clone();
+    inconsistent.tau_g2[2] = inconsistent.tau_g2[3];
+    assert!(inconsistent.verify(&mut rng).is_err());
+    let mut inconsistent = phase1.clone();
+    inconsistent.beta_g2 = inconsistent.tau_g2[1];
+    assert!(inconsistent.verify(&mut rng).is_err());
+    phase1.verify(&mut rng).unwrap();
+
+    // a malformed power or section size is an error, not a panic or a huge allocation
+    let mut too_many = sections.clone();
+    too_many[0].1[4 + 48..4 + 48 + 4].copy_from_slice(&40u32.to_le_bytes());
+    assert!(Phase1::read_ptau(&build(&too_many)[..]).is_err());
+    let mut oversized = ptau;
+    oversized[16..24].copy_from_slice(&u64::MAX.to_le_bytes());
+    assert!(Phase1::read_ptau(&oversized[..]).is_err());
+}
+
+#[test]
+fn test_powersoftau_challenge_and_response() {
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use ark_std::UniformRand;
+    use ark_ff::BigInteger;
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let n = 4;
+    let phase1 = phase1_from_secrets(n, Fr::rand(&mut rng), Fr::rand(&mut rng), Fr::rand(&mut rng));
+
+    // zcash layout: big-endian, c1 before c0, flags in the first byte
+    let be = |x: &Fq| x.into_repr().to_bytes_be();
+    let flags = |infinity: bool, compressed: bool, greatest: bool| {
+        (if compressed { COMPRESSED } else { 0 }) | (if infinity { INFINITY } else { 0 }) | (if greatest { GREATEST } else { 0 })
+    };
+    let write_g1 = |out: &mut Vec<u8>, p: &G1Affine, compressed: bool| {
+        let start = out.len();
+        if p.is_zero() {
+            out.extend(vec![0u8; if compressed { 48 } else { 96 }]);
+        } else {
+            out.extend(be(&p.x));
+            if !compressed {
+                out.extend(be(&p.y));
+            }
+        }
+        out[start] |= flags(p.is_zero(), compressed, compressed && !p.is_zero() && p.y > -p.y);
+    };
+    let write_g2 = |out: &mut Vec<u8>, p: &G2Affine, compressed: bool| {
+        let start = out.len();
+        out.extend(be(&p.x.c1));
+        out.extend(be(&p.x.c0));
+        if !compressed {
+            out.extend(be(&p.y.c1));
+            out.extend(be(&p.y.c0));
+        }
+        out[start] |= flags(false, compressed, compressed && p.y > -p.y);
+    };
+    let write = |phase1: &Phase1, compressed: bool| {
+        let mut out = vec![0u8; POWERSOFTAU_HASH_SIZE];
+        phase1.tau_g1.iter().for_each(|p| write_g1(&mut out, p, compressed));
+        phase1.tau_g2.iter().for_each(|p| write_g2(&mut out, p, compressed));
+        phase1.alpha_tau_g1.iter().for_each(|p| write_g1(&mut out, p, compressed));
+        phase1.beta_tau_g1.iter().for_each(|p| write_g1(&mut out, p, compressed));
+        write_g2(&mut out, &phase1.beta_g2, compressed);
+        out
+    };
+
+    let challenge = write(&phase1, false);
+    assert_eq!(Phase1::read_powersoftau(&challenge[..], n, false).unwrap(), phase1);
+    assert!(Phase1::read_powersoftau(&challenge[..], n, true).is_err());
+
+    let mut response = write(&phase1, true);
+    response.extend(vec![0u8; POWERSOFTAU_PUBLIC_KEY_SIZE]);
+    assert_eq!(Phase1::read_powersoftau(&response[..], n, true).unwrap(), phase1);
+
+    // the file size gives the number of powers and the compression
+    for (name, bytes) in [("challenge", &challenge), ("response", &response)] {
+        let path = std::env::temp_dir().join(format!("arkworks-example-phase1-test-{}", name));
+        std::fs::write(&path, bytes).unwrap();
+        let loaded = Phase1::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, phase1);
+    }
+
+    // a flipped sign flag gives -P, which is a different point
+    let mut flipped = response.clone();
+    flipped[POWERSOFTAU_HASH_SIZE + 48] ^= GREATEST;
+    let decoded = Phase1::read_powersoftau(&flipped[..], n, true).unwrap();
+    assert_eq!(decoded.tau_g1[1], -phase1.tau_g1[1]);
+    assert!(decoded.verify(&mut rng).is_err());
+
+    // the encoding of infinity parses, but such powers do not verify
+    let mut with_infinity = phase1.clone();
+    with_infinity.alpha_tau_g1[1] = G1Affine::zero();
+    let decoded = Phase1::read_powersoftau(&write(&with_infinity, true)[..], n, true).unwrap();
+    assert_eq!(decoded, with_infinity);
+    assert!(decoded.verify(&mut rng).is_err());
+}
@@ -0,0 +1,224 @@
+use ark_bls12_377::Bls12_377;
+use ark_crypto_primitives::snark::{BooleanInputVar, FromFieldElementsGadget, SNARKGadget};
+use ark_crypto_primitives::Error;
+use ark_ec::PairingEngine;
+use ark_ff::Zero;
+use ark_groth16::constraints::{Groth16VerifierGadget, ProofVar, VerifyingKeyVar};
+use ark_groth16::{Proof, ProvingKey, VerifyingKey};
+use ark_r1cs_std::pairing::PairingVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_std::marker::PhantomData;
+use ark_std::rand::{CryptoRng, RngCore};
+
+use crate::CubeDemo;
+
+// A Groth16 proof over a pairing engine E is verified in a circuit over E's base field, which is
+// proved with a second engine whose scalar field is that base field.
+// BLS12-377 and BW6-761 form a two-chain: the base field of BLS12-377 is the scalar field of BW6-761.
+// There is no pairing-friendly curve over the BW6-761 base field, so this gives one level of
+// recursion, which can still check several inner proofs in one outer proof.
+// MNT4-298 and MNT6-298 form a cycle: each curve's base field is the other's scalar field, so the
+// levels alternate between the two curves and can go on, see `test_recursion_two_levels`.
+pub type InnerFr = ark_bls12_377::Fr;
+pub type OuterFr = ark_bw6_761::Fr;
+
+type InnerPairingVar = ark_bls12_377::constraints::PairingVar;
+
+// the existing `CubeDemo` setup and prove flow, over E
+pub fn inner_setup<E: PairingEngine, R: RngCore + CryptoRng>(rng: &mut R) -> Result<ProvingKey<E>, Error> {
+    let blank = CubeDemo { x: E::Fr::zero() };
+    Ok(ark_groth16::generate_random_parameters::<E, _, _>(blank, rng)?)
+}
+
+// proves x^3 + x == out, returning the proof and the public out
+pub fn inner_prove<E: PairingEngine, R: RngCore + CryptoRng>(
+    pk: &ProvingKey<E>,
+    x: E::Fr,
+    rng: &mut R,
+) -> Result<(Proof<E>, E::Fr), Error> {
+    let out = x * x * x + x;
+    let proof = ark_groth16::create_random_proof(CubeDemo { x }, pk, rng)?;
+    Ok((proof, out))
+}
+
+// proving that every inner proof verifies against the inner verifying key for its public inputs
+pub struct RecursiveDemo<E: PairingEngine = Bls12_377, P: PairingVar<E> = InnerPairingVar> {
+    pub vk: VerifyingKey<E>,
+    pub proofs: Vec<(Proof<E>, Vec<E::Fr>)>,
+    _pairing: PhantomData<P>,
+}
+
+// derive would require P: Clone
+impl<E: PairingEngine, P: PairingVar<E>> Clone for RecursiveDemo<E, P> {
+    fn clone(&self) -> Self {
+        RecursiveDemo::new(self.vk.clone(), self.proofs.clone())
+    }
+}
+
+impl<E: PairingEngine, P: PairingVar<E>> RecursiveDemo<E, P> {
+    pub fn new(vk: VerifyingKey<E>, proofs: Vec<(Proof<E>, Vec<E::Fr>)>) -> Self {
+        RecursiveDemo { vk, proofs, _pairing: PhantomData }
+    }
+
+    // a circuit checking num_proofs inner proofs, for setup
+    pub fn blank(vk: VerifyingKey<E>, num_proofs: usize) -> Self {
+        let input = vec![E::Fr::zero(); vk.gamma_abc_g1.len() - 1];
+        RecursiveDemo::new(vk, vec![(Proof::default(), input); num_proofs])
+    }
+}
+
+impl<E: PairingEngine, P: PairingVar<E>> ConstraintSynthesizer<E::Fq> for RecursiveDemo<E, P> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<E::Fq>) -> Result<(), SynthesisError> {
+
+        // the inner key is fixed by the outer setup
+        let vk = VerifyingKeyVar::<E, P>::new_constant(ark_relations::ns!(cs, "inner vk"), &self.vk)?;
+
+        for (proof, input) in self.proofs.iter() {
+            let input = BooleanInputVar::<E::Fr, E::Fq>::new_input(
+                ark_relations::ns!(cs, "inner public input"), || Ok(input.clone())
+            )?;
+            let proof = ProofVar::<E, P>::new_witness(
+                ark_relations::ns!(cs, "inner proof"), || Ok(proof.clone())
+            )?;
+
+            Groth16VerifierGadget::<E, P>::verify(&vk, &input, &proof)?.enforce_equal(&Boolean::TRUE)?;
+        }
+
+        Ok(())
+    }
+}
+
+// the outer statement: the public inputs of each inner proof, repacked into E's base field the
+// way `BooleanInputVar` allocates them; a BLS12-377 scalar fits in one BW6-761 scalar
+pub fn public_input<E: PairingEngine>(inputs: &[Vec<E::Fr>]) -> Vec<E::Fq> {
+    inputs.iter().flat_map(BooleanInputVar::<E::Fr, E::Fq>::repack_input).collect()
+}
+
+// O is the outer engine, whose scalar field is E's base field
+pub fn outer_setup<E, P, O, R>(vk: &VerifyingKey<E>, num_proofs: usize, rng: &mut R) -> Result<ProvingKey<O>, Error>
+where
+    E: PairingEngine,
+    P: PairingVar<E>,
+    O: PairingEngine<Fr = E::Fq>,
+    R: RngCore + CryptoRng,
+{
+    let blank = RecursiveDemo::<E, P>::blank(vk.clone(), num_proofs);
+    Ok(ark_groth16::generate_random_parameters::<O, _, _>(blank, rng)?)
+}
+
+#[test]
+fn test_recursion_one_proof() {
+    use ark_bw6_761::BW6_761;
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use ark_groth16::*;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let inner_pk = inner_setup::<Bls12_377, _>(&mut rng).unwrap();
+    let (proof, out) = inner_prove(&inner_pk, InnerFr::from(3u64), &mut rng).unwrap();
+    assert_eq!(out, InnerFr::from(30u64));
+    assert!(verify_proof(&prepare_verifying_key(&inner_pk.vk), &proof, &[out]).unwrap());
+
+    let circuit = RecursiveDemo::<Bls12_377>::new(inner_pk.vk.clone(), vec![(proof.clone(), vec![out])]);
+
+    let cs = ConstraintSystem::<OuterFr>::new_ref();
+    circuit.clone().generate_constraints(cs.clone()).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+    println!("verifying one inner proof: {} constraints", cs.num_constraints());
+
+    let outer_pk = outer_setup::<_, InnerPairingVar, BW6_761, _>(&inner_pk.vk, 1, &mut rng).unwrap();
+    let outer_proof = create_random_proof(circuit, &outer_pk, &mut rng).unwrap();
+    let pvk = prepare_verifying_key(&outer_pk.vk);
+
+    assert!(verify_proof(&pvk, &outer_proof, &public_input::<Bls12_377>(&[vec![out]])).unwrap());
+    assert!(!verify_proof(&pvk, &outer_proof, &public_input::<Bls12_377>(&[vec![InnerFr::from(31u64)]])).unwrap());
+
+    // an inner proof for a different out does not satisfy the outer circuit
+    let cs = ConstraintSystem::<OuterFr>::new_ref();
+    RecursiveDemo::<Bls12_377>::new(inner_pk.vk, vec![(proof, vec![InnerFr::from(31u64)])])
+        .generate_constraints(cs.clone()).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn test_outer_proof_batches_two_inner_proofs() {
+    use ark_bw6_761::BW6_761;
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use ark_groth16::*;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let inner_pk = inner_setup::<Bls12_377, _>(&mut rng).unwrap();
+    let first = inner_prove(&inner_pk, InnerFr::from(3u64), &mut rng).unwrap();
+    let second = inner_prove(&inner_pk, InnerFr::from(4u64), &mut rng).unwrap();
+    let outs = [vec![first.1], vec![second.1]];
+
+    let circuit = RecursiveDemo::<Bls12_377>::new(
+        inner_pk.vk.clone(), vec![(first.0.clone(), outs[0].clone()), (second.0.clone(), outs[1].clone())]
+    );
+
+    let outer_pk = outer_setup::<_, InnerPairingVar, BW6_761, _>(&inner_pk.vk, 2, &mut rng).unwrap();
+    let outer_proof = create_random_proof(circuit, &outer_pk, &mut rng).unwrap();
+    let pvk = prepare_verifying_key(&outer_pk.vk);
+
+    // one outer proof stands for both inner ones
+    assert!(verify_proof(&pvk, &outer_proof, &public_input::<Bls12_377>(&outs)).unwrap());
+    assert!(!verify_proof(&pvk, &outer_proof, &public_input::<Bls12_377>(&[outs[1].clone(), outs[0].clone()])).unwrap());
+
+    // swapping the proofs breaks the binding to the outs
+    let cs = ConstraintSystem::<OuterFr>::new_ref();
+    RecursiveDemo::<Bls12_377>::new(inner_pk.vk, vec![(first.0, outs[1].clone()), (second.0, outs[0].clone())])
+        .generate_constraints(cs.clone()).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn test_recursion_two_levels() {
+    use ark_mnt4_298::{constraints::PairingVar as MNT4PairingVar, MNT4_298};
+    use ark_mnt6_298::{constraints::PairingVar as MNT6PairingVar, MNT6_298};
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use ark_groth16::*;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    // level 0: the cube proof over MNT4-298
+    let pk0 = inner_setup::<MNT4_298, _>(&mut rng).unwrap();
+    let x = ark_mnt4_298::Fr::from(3u64);
+    let (proof0, out) = inner_prove(&pk0, x, &mut rng).unwrap();
+    assert_eq!(out, ark_mnt4_298::Fr::from(30u64));
+
+    // level 1: an MNT6-298 proof that the level 0 proof verifies
+    let input1 = public_input::<MNT4_298>(&[vec![out]]);
+    let pk1 = outer_setup::<_, MNT4PairingVar, MNT6_298, _>(&pk0.vk, 1, &mut rng).unwrap();
+    let circuit1 = RecursiveDemo::<MNT4_298, MNT4PairingVar>::new(pk0.vk.clone(), vec![(proof0, vec![out])]);
+    let proof1 = create_random_proof(circuit1, &pk1, &mut rng).unwrap();
+    assert!(verify_proof(&prepare_verifying_key(&pk1.vk), &proof1, &input1).unwrap());
+
+    // level 2: an MNT4-298 proof that the level 1 proof verifies, back on the first curve
+    let input2 = public_input::<MNT6_298>(std::slice::from_ref(&input1));
+    let pk2 = outer_setup::<_, MNT6PairingVar, MNT4_298, _>(&pk1.vk, 1, &mut rng).unwrap();
+    let circuit2 = RecursiveDemo::<MNT6_298, MNT6PairingVar>::new(pk1.vk.clone(), vec![(proof1.clone(), input1)]);
+
+    let cs = ConstraintSystem::<ark_mnt4_298::Fr>::new_ref();
+    circuit2.clone().generate_constraints(cs.clone()).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+    println!("verifying a level 1 proof: {} constraints", cs.num_constraints());
+
+    let proof2 = create_random_proof(circuit2, &pk2, &mut rng).unwrap();
+    let pvk2 = prepare_verifying_key(&pk2.vk);
+    assert!(verify_proof(&pvk2, &proof2, &input2).unwrap());
+
+    // the level 2 statement still pins the original out
+    let wrong = public_input::<MNT4_298>(&[vec![ark_mnt4_298::Fr::from(31u64)]]);
+    assert!(!verify_proof(&pvk2, &proof2, &public_input::<MNT6_298>(std::slice::from_ref(&wrong))).unwrap());
+
+    // a level 1 proof does not verify for another level 0 statement
+    let cs = ConstraintSystem::<ark_mnt4_298::Fr>::new_ref();
+    RecursiveDemo::<MNT6_298, MNT6PairingVar>::new(pk1.vk, vec![(proof1, wrong)])
+        .generate_constraints(cs.clone()).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+}
@@ -0,0 +1,87 @@
+use ark_std::rand::rngs::{OsRng, StdRng};
+use ark_std::rand::{CryptoRng, Error, RngCore, SeedableRng};
+
+// where setup and proving randomness comes from: OS entropy unless a seed is asked for explicitly.
+// With a known seed anyone can recompute the toxic waste of the setup and the blinding of
+// every proof, so seeded runs are for reproducible tests only.
+// there is one per run, so the seeded state is not boxed
+#[allow(clippy::large_enum_variant)]
+pub enum ProtocolRng {
+    Os(OsRng),
+    InsecureSeed(u64, StdRng),
+}
+
+impl ProtocolRng {
+    pub fn os() -> Self {
+        ProtocolRng::Os(OsRng)
+    }
+
+    pub fn insecure_seed(seed: u64) -> Self {
+        ProtocolRng::InsecureSeed(seed, StdRng::seed_from_u64(seed))
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        match self {
+            ProtocolRng::Os(_) => None,
+            ProtocolRng::InsecureSeed(seed, _) => Some(*seed),
+        }
+    }
+
+    // the line embedded in artifacts produced with a fixed seed
+    pub fn warning(&self) -> Option<String> {
+        self.seed().map(insecure_seed_warning)
+    }
+}
+
+// also printed for keys saved from a seeded setup, see `keys::KeyHeader`
+pub fn insecure_seed_warning(seed: u64) -> String {
+    format!(
+        "WARNING: generated with --insecure-seed {}, the setup and proofs are reproducible and not zero-knowledge",
+        seed
+    )
+}
+
+impl RngCore for ProtocolRng {
+    fn next_u32(&mut self) -> u32 {
+        match self {
+            ProtocolRng::Os(rng) => rng.next_u32(),
+            ProtocolRng::InsecureSeed(_, rng) => rng.next_u32(),
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        match self {
+            ProtocolRng::Os(rng) => rng.next_u64(),
+            ProtocolRng::InsecureSeed(_, rng) => rng.next_u64(),
+        }
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        match self {
+            ProtocolRng::Os(rng) => rng.fill_bytes(dest),
+            ProtocolRng::InsecureSeed(_, rng) => rng.fill_bytes(dest),
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        match self {
+            ProtocolRng::Os(rng) => rng.try_fill_bytes(dest),
+            ProtocolRng::InsecureSeed(_, rng) => rng.try_fill_bytes(dest),
+        }
+    }
+}
+
+// both sources are cryptographically secure, a fixed seed only makes them predictable
+impl CryptoRng for ProtocolRng {}
+
+#[test]
+fn test_rng_policy() {
+    let (mut a, mut b) = (ProtocolRng::insecure_seed(7), ProtocolRng::insecure_seed(7));
+    assert_eq!(a.next_u64(), b.next_u64());
+    assert!(a.warning().unwrap().contains("--insecure-seed 7"));
+    assert_eq!(a.seed(), Some(7));
+
+    let (mut a, mut b) = (ProtocolRng::os(), ProtocolRng::os());
+    assert_ne!((a.next_u64(), a.next_u64()), (b.next_u64(), b.next_u64()));
+    assert!(a.warning().is_none());
+}
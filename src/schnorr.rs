@@ -0,0 +1,184 @@
+use ark_bls12_381::Fr;
+use ark_crypto_primitives::crh::{pedersen, CRHGadget, CRH};
+use ark_crypto_primitives::Error;
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ed_on_bls12_381::{
+    constraints::EdwardsVar, EdwardsAffine, EdwardsProjective as JubJub, Fr as JubJubScalar,
+};
+use ark_ff::{to_bytes, BigInteger, PrimeField, UniformRand};
+use ark_r1cs_std::prelude::*;
+use ark_r1cs_std::uint8::UInt8;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_std::rand::Rng;
+
+// length in bytes of the signed message
+pub const MSG_LEN: usize = 32;
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct ChallengeWindow;
+
+// the challenge hashes R (64 bytes) || pk (64 bytes) || message (32 bytes)
+// `WINDOW_SIZE * NUM_WINDOWS` = 160 * 8 bits
+impl pedersen::Window for ChallengeWindow {
+    const WINDOW_SIZE: usize = 4;
+    const NUM_WINDOWS: usize = 320;
+}
+
+pub type ChallengeCRH = pedersen::CRH<JubJub, ChallengeWindow>;
+type ChallengeCRHGadget = pedersen::constraints::CRHGadget<JubJub, EdwardsVar, ChallengeWindow>;
+
+pub type SecretKey = JubJubScalar;
+pub type PublicKey = EdwardsAffine;
+
+// Schnorr signature (R, s) with s = k + e * sk and e = H(R || pk || m)
+#[derive(Clone, Debug, PartialEq)]
+pub struct Signature {
+    pub r: EdwardsAffine,
+    pub s: JubJubScalar,
+}
+
+pub fn keygen<R: Rng>(rng: &mut R) -> (SecretKey, PublicKey) {
+    let sk = JubJubScalar::rand(rng);
+    let pk = EdwardsAffine::prime_subgroup_generator().mul(sk.into_repr()).into_affine();
+    (sk, pk)
+}
+
+// the challenge is the x coordinate of the Pedersen hash reduced into the JubJub scalar field,
+// which is what multiplying by its bits inside the circuit amounts to
+fn challenge(
+    params: &pedersen::Parameters<JubJub>,
+    r: &EdwardsAffine,
+    pk: &PublicKey,
+    message: &[u8; MSG_LEN],
+) -> Result<JubJubScalar, Error> {
+    let mut input = to_bytes![r, pk]?;
+    input.extend_from_slice(message);
+    let h = ChallengeCRH::evaluate(params, &input)?;
+    Ok(JubJubScalar::from_le_bytes_mod_order(&h.x.into_repr().to_bytes_le()))
+}
+
+pub fn sign<R: Rng>(
+    params: &pedersen::Parameters<JubJub>,
+    sk: &SecretKey,
+    message: &[u8; MSG_LEN],
+    rng: &mut R,
+) -> Result<Signature, Error> {
+    let generator = EdwardsAffine::prime_subgroup_generator();
+    let pk = generator.mul(sk.into_repr()).into_affine();
+
+    let k = JubJubScalar::rand(rng);
+    let r = generator.mul(k.into_repr()).into_affine();
+    let e = challenge(params, &r, &pk, message)?;
+
+    Ok(Signature { r, s: k + e * sk })
+}
+
+// G * s == R + pk * e
+pub fn verify(
+    params: &pedersen::Parameters<JubJub>,
+    pk: &PublicKey,
+    message: &[u8; MSG_LEN],
+    signature: &Signature,
+) -> Result<bool, Error> {
+    let e = challenge(params, &signature.r, pk, message)?;
+    let lhs = EdwardsAffine::prime_subgroup_generator().mul(signature.s.into_repr());
+    let rhs = signature.r.into_projective() + pk.mul(e.into_repr());
+    Ok(lhs == rhs)
+}
+
+// proving that I hold a valid signature from the public key pk on a hidden message
+#[derive(Clone)]
+pub struct SignatureDemo {
+    pub params: pedersen::Parameters<JubJub>,
+    pub pk: PublicKey,
+    pub message: [u8; MSG_LEN],
+    pub signature: Signature,
+}
+
+impl ConstraintSynthesizer<Fr> for SignatureDemo {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+
+        // the public key is the only public input: (pk.x, pk.y)
+        let pk = <EdwardsVar as AllocVar<EdwardsAffine, _>>::new_input(
+            ark_relations::ns!(cs, "public key"), || Ok(self.pk)
+        )?;
+
+        let message = UInt8::new_witness_vec(ark_relations::ns!(cs, "message"), &self.message)?;
+
+        let r = <EdwardsVar as AllocVar<EdwardsAffine, _>>::new_witness(
+            ark_relations::ns!(cs, "signature r"), || Ok(self.signature.r)
+        )?;
+        let s_bits = Vec::<Boolean<Fr>>::new_witness(
+            ark_relations::ns!(cs, "signature s"), || Ok(self.signature.s.into_repr().to_bits_le())
+        )?;
+
+        let params = pedersen::constraints::CRHParametersVar::new_constant(
+            ark_relations::ns!(cs, "parameters"), &self.params
+        )?;
+
+        let mut hash_input = r.to_bytes()?;
+        hash_input.extend(pk.to_bytes()?);
+        hash_input.extend(message);
+        let e_bits = ChallengeCRHGadget::evaluate(&params, &hash_input)?.x.to_bits_le()?;
+
+        let generator = <EdwardsVar as AllocVar<EdwardsAffine, _>>::new_constant(
+            ark_relations::ns!(cs, "generator"), EdwardsAffine::prime_subgroup_generator()
+        )?;
+
+        let lhs = generator.scalar_mul_le(s_bits.iter())?;
+        let rhs = r + pk.scalar_mul_le(e_bits.iter())?;
+        lhs.enforce_equal(&rhs)?;
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_signature_proof() {
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use ark_bls12_381::Bls12_381;
+    use ark_groth16::*;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let params = ChallengeCRH::setup(&mut rng).unwrap();
+    let (sk, pk) = keygen(&mut rng);
+    let message = [42u8; MSG_LEN];
+    let signature = sign(&params, &sk, &message, &mut rng).unwrap();
+    assert!(verify(&params, &pk, &message, &signature).unwrap());
+
+    let circuit = SignatureDemo { params, pk, message, signature };
+
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    circuit.clone().generate_constraints(cs.clone()).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+    println!("num constraints: {:?}", cs.num_constraints());
+
+    let param = generate_random_parameters::<Bls12_381, _, _>(circuit.clone(), &mut rng).unwrap();
+    let proof = create_random_proof(circuit, &param, &mut rng).unwrap();
+    let pvk = prepare_verifying_key(&param.vk);
+
+    let statement = vec![pk.x, pk.y];
+    let result = verify_proof(&pvk, &proof, &statement).unwrap();
+    assert!(result);
+}
+
+#[test]
+fn test_signature_wrong_message() {
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use ark_relations::r1cs::ConstraintSystem;
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let params = ChallengeCRH::setup(&mut rng).unwrap();
+    let (sk, pk) = keygen(&mut rng);
+    let signature = sign(&params, &sk, &[1u8; MSG_LEN], &mut rng).unwrap();
+    assert!(!verify(&params, &pk, &[2u8; MSG_LEN], &signature).unwrap());
+
+    let circuit = SignatureDemo { params, pk, message: [2u8; MSG_LEN], signature };
+
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    circuit.generate_constraints(cs.clone()).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+}
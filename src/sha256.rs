@@ -0,0 +1,226 @@
+use ark_bls12_381::Fr;
+use ark_ff::ToConstraintField;
+use ark_r1cs_std::prelude::*;
+use ark_r1cs_std::uint32::UInt32;
+use ark_r1cs_std::uint8::UInt8;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+
+pub const DIGEST_LEN: usize = 32;
+
+const IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+// native reference digest
+pub fn sha256(message: &[u8]) -> [u8; DIGEST_LEN] {
+    use sha2::{Digest, Sha256};
+
+    let mut digest = [0u8; DIGEST_LEN];
+    digest.copy_from_slice(&Sha256::digest(message));
+    digest
+}
+
+// the padding only depends on the message length, so it is added as constants:
+// 0x80, zero bytes up to 56 mod 64, then the bit length as a big-endian u64
+fn pad(message: &[UInt8<Fr>]) -> Vec<UInt8<Fr>> {
+    let mut padded = message.to_vec();
+    padded.push(UInt8::constant(0x80));
+    while padded.len() % 64 != 56 {
+        padded.push(UInt8::constant(0));
+    }
+    let bit_len = (message.len() as u64) * 8;
+    padded.extend(UInt8::constant_vec(&bit_len.to_be_bytes()));
+    padded
+}
+
+fn shr(x: &UInt32<Fr>, by: usize) -> UInt32<Fr> {
+    let bits = x.to_bits_le();
+    let shifted: Vec<_> = (0..32)
+        .map(|i| if i + by < 32 { bits[i + by].clone() } else { Boolean::FALSE })
+        .collect();
+    UInt32::from_bits_le(&shifted)
+}
+
+fn xor3(a: &UInt32<Fr>, b: &UInt32<Fr>, c: &UInt32<Fr>) -> Result<UInt32<Fr>, SynthesisError> {
+    a.xor(b)?.xor(c)
+}
+
+// ch(e, f, g) = g ^ (e & (f ^ g))
+fn ch(e: &UInt32<Fr>, f: &UInt32<Fr>, g: &UInt32<Fr>) -> Result<UInt32<Fr>, SynthesisError> {
+    let bits = e.to_bits_le().iter()
+        .zip(f.to_bits_le().iter().zip(g.to_bits_le().iter()))
+        .map(|(e, (f, g))| g.xor(&e.and(&f.xor(g)?)?))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(UInt32::from_bits_le(&bits))
+}
+
+// maj(a, b, c) = (a & b) ^ (c & (a ^ b))
+fn maj(a: &UInt32<Fr>, b: &UInt32<Fr>, c: &UInt32<Fr>) -> Result<UInt32<Fr>, SynthesisError> {
+    let bits = a.to_bits_le().iter()
+        .zip(b.to_bits_le().iter().zip(c.to_bits_le().iter()))
+        .map(|(a, (b, c))| a.and(b)?.xor(&c.and(&a.xor(b)?)?))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(UInt32::from_bits_le(&bits))
+}
+
+fn compress(state: &mut [UInt32<Fr>], block: &[UInt8<Fr>]) -> Result<(), SynthesisError> {
+    let mut w = Vec::with_capacity(64);
+    for word in block.chunks(4) {
+        // big-endian word from little-endian bits: the last byte holds the lowest bits
+        let mut bits = Vec::with_capacity(32);
+        for byte in word.iter().rev() {
+            bits.extend(byte.to_bits_le()?);
+        }
+        w.push(UInt32::from_bits_le(&bits));
+    }
+    for i in 16..64 {
+        let s0 = xor3(&w[i - 15].rotr(7), &w[i - 15].rotr(18), &shr(&w[i - 15], 3))?;
+        let s1 = xor3(&w[i - 2].rotr(17), &w[i - 2].rotr(19), &shr(&w[i - 2], 10))?;
+        w.push(UInt32::addmany(&[w[i - 16].clone(), s0, w[i - 7].clone(), s1])?);
+    }
+
+    let mut v = state.to_vec();
+    for i in 0..64 {
+        let s1 = xor3(&v[4].rotr(6), &v[4].rotr(11), &v[4].rotr(25))?;
+        let temp1 = UInt32::addmany(&[
+            v[7].clone(), s1, ch(&v[4], &v[5], &v[6])?, UInt32::constant(K[i]), w[i].clone(),
+        ])?;
+        let s0 = xor3(&v[0].rotr(2), &v[0].rotr(13), &v[0].rotr(22))?;
+        let temp2 = UInt32::addmany(&[s0, maj(&v[0], &v[1], &v[2])?])?;
+
+        v[7] = v[6].clone();
+        v[6] = v[5].clone();
+        v[5] = v[4].clone();
+        v[4] = UInt32::addmany(&[v[3].clone(), temp1.clone()])?;
+        v[3] = v[2].clone();
+        v[2] = v[1].clone();
+        v[1] = v[0].clone();
+        v[0] = UInt32::addmany(&[temp1, temp2])?;
+    }
+
+    for (s, v) in state.iter_mut().zip(v) {
+        *s = UInt32::addmany(&[s.clone(), v])?;
+    }
+    Ok(())
+}
+
+// in-circuit SHA-256 of a byte string whose length is fixed at setup time
+pub fn sha256_gadget(message: &[UInt8<Fr>]) -> Result<Vec<UInt8<Fr>>, SynthesisError> {
+    let mut state: Vec<_> = IV.iter().map(|&h| UInt32::constant(h)).collect();
+    for block in pad(message).chunks(64) {
+        compress(&mut state, block)?;
+    }
+
+    let mut digest = Vec::with_capacity(DIGEST_LEN);
+    for word in state {
+        let bits = word.to_bits_le();
+        for byte in bits.chunks(8).rev() {
+            digest.push(UInt8::from_bits_le(byte));
+        }
+    }
+    Ok(digest)
+}
+
+// proving that I know a message whose SHA-256 digest is the public digest
+#[derive(Clone)]
+pub struct Sha256Demo {
+    pub message: Vec<u8>,
+    pub digest: [u8; DIGEST_LEN],
+}
+
+impl ConstraintSynthesizer<Fr> for Sha256Demo {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+
+        // the digest is packed into field elements, see `public_input`
+        let digest = UInt8::new_input_vec(ark_relations::ns!(cs, "digest"), &self.digest)?;
+
+        let mut message = vec![];
+        for byte in self.message.iter() {
+            message.push(UInt8::new_witness(ark_relations::ns!(cs, "preimage"), || Ok(byte))?);
+        }
+
+        let result = sha256_gadget(&message)?;
+        result.enforce_equal(&digest)?;
+
+        Ok(())
+    }
+}
+
+// the verifier statement for a digest
+pub fn public_input(digest: &[u8; DIGEST_LEN]) -> Vec<Fr> {
+    digest.to_field_elements().unwrap()
+}
+
+// number of constraints for a message of `message_len` bytes, `stats sha256 --size` reports the rest
+#[cfg(test)]
+pub fn num_constraints(message_len: usize) -> usize {
+    use ark_relations::r1cs::{ConstraintSystem, SynthesisMode};
+
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    cs.set_mode(SynthesisMode::Setup);
+    let circuit = Sha256Demo {
+        message: vec![0u8; message_len],
+        digest: [0u8; DIGEST_LEN],
+    };
+    circuit.generate_constraints(cs.clone()).unwrap();
+    cs.num_constraints()
+}
+
+#[test]
+fn test_sha256_proof() {
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use ark_bls12_381::Bls12_381;
+    use ark_groth16::*;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let message = b"hello arkworks".to_vec();
+    let digest = sha256(&message);
+    let circuit = Sha256Demo { message, digest };
+
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    circuit.clone().generate_constraints(cs.clone()).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+
+    let param = generate_random_parameters::<Bls12_381, _, _>(circuit.clone(), &mut rng).unwrap();
+    let proof = create_random_proof(circuit, &param, &mut rng).unwrap();
+    let pvk = prepare_verifying_key(&param.vk);
+
+    let result = verify_proof(&pvk, &proof, &public_input(&digest)).unwrap();
+    assert!(result);
+
+    let mut wrong_digest = digest;
+    wrong_digest[0] ^= 1;
+    let result = verify_proof(&pvk, &proof, &public_input(&wrong_digest)).unwrap();
+    assert!(!result);
+}
+
+#[test]
+fn test_sha256_gadget_matches_native() {
+    use ark_relations::r1cs::ConstraintSystem;
+
+    // lengths around the 55/56 and 64 byte padding boundaries
+    for len in [0usize, 3, 55, 56, 64, 100] {
+        let message: Vec<u8> = (0..len).map(|i| i as u8).collect();
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let message_var = UInt8::new_witness_vec(cs.clone(), &message).unwrap();
+        let digest = sha256_gadget(&message_var).unwrap().value().unwrap();
+
+        assert_eq!(digest, sha256(&message).to_vec());
+        assert!(cs.is_satisfied().unwrap());
+        println!("message length {}: {} constraints", len, num_constraints(len));
+    }
+}
@@ -0,0 +1,251 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use ark_bls12_381::Fr;
+use ark_crypto_primitives::Error;
+use ark_relations::r1cs::{ConstraintLayer, ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, OptimizationGoal, SynthesisMode};
+use tracing::span::Id;
+use tracing::{Metadata, Subscriber};
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Registry;
+
+// constraints and variables added while a namespace was entered, nested namespaces included
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Counts {
+    pub constraints: usize,
+    pub witnesses: usize,
+    pub inputs: usize,
+}
+
+impl Counts {
+    fn of(cs: &ConstraintSystemRef<Fr>) -> Self {
+        Counts {
+            constraints: cs.num_constraints(),
+            witnesses: cs.num_witness_variables(),
+            inputs: cs.num_instance_variables(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Report {
+    pub constraints: usize,
+    // not counting the constant one
+    pub public_inputs: usize,
+    pub witnesses: usize,
+    pub a_non_zero: usize,
+    pub b_non_zero: usize,
+    pub c_non_zero: usize,
+    // by `ns!` path, nested namespaces separated by '/'; see `is_namespace`
+    pub namespaces: BTreeMap<String, Counts>,
+    // added outside any namespace, so the top-level rows and this one sum to the totals
+    pub outside: Counts,
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "constraints: {}", self.constraints)?;
+        writeln!(f, "public inputs: {}", self.public_inputs)?;
+        writeln!(f, "witnesses: {}", self.witnesses)?;
+        writeln!(f, "non-zero entries: A {}, B {}, C {}", self.a_non_zero, self.b_non_zero, self.c_non_zero)?;
+        writeln!(f, "{:>12} {:>12} {:>12}  namespace", "constraints", "witnesses", "inputs")?;
+        for (path, counts) in self.namespaces.iter() {
+            writeln!(f, "{:>12} {:>12} {:>12}  {}", counts.constraints, counts.witnesses, counts.inputs, path)?;
+        }
+        let outside = self.outside;
+        writeln!(f, "{:>12} {:>12} {:>12}  (outside any namespace)", outside.constraints, outside.witnesses, outside.inputs)?;
+        Ok(())
+    }
+}
+
+// where each namespace's constraints and variables came from
+#[derive(Clone, Debug, Default)]
+pub struct Trace {
+    pub namespaces: BTreeMap<String, Counts>,
+    // innermost namespace path of each constraint, witness and instance variable, "" outside any
+    pub constraint_paths: Vec<String>,
+    pub witness_paths: Vec<String>,
+    pub input_paths: Vec<String>,
+}
+
+// the constraint system being measured and the namespaces entered so far, for `NamespaceLayer`.
+// A constraint system is not `Send`, so it cannot live in the layer itself.
+struct Tracker {
+    cs: ConstraintSystemRef<Fr>,
+    stack: Vec<(String, Counts)>,
+    trace: Trace,
+}
+
+impl Tracker {
+    // everything added since the last span event belongs to the innermost open namespace
+    fn attribute(&mut self) -> Counts {
+        let counts = Counts::of(&self.cs);
+        let path = self.stack.last().map_or("", |(path, _)| path.as_str());
+        let trace = &mut self.trace;
+        trace.constraint_paths.resize(counts.constraints, path.to_string());
+        trace.witness_paths.resize(counts.witnesses, path.to_string());
+        trace.input_paths.resize(counts.inputs, path.to_string());
+        counts
+    }
+}
+
+thread_local! {
+    static TRACKER: RefCell<Option<Tracker>> = const { RefCell::new(None) };
+}
+
+// `ns!` labels written in this crate's circuits. Gadgets from dependencies open r1cs spans of their
+// own, for instrumented functions like `enforce_equal` and for their internal `ns!` labels; what
+// they add is counted in the enclosing namespace instead (`check` shows those spans separately)
+fn is_namespace(metadata: &Metadata<'_>) -> bool {
+    let this_crate = module_path!().split("::").next();
+    metadata.target() == "r1cs" && metadata.module_path().is_some_and(|path| path.split("::").next() == this_crate)
+}
+
+// snapshots the counts when a namespace is entered and adds the difference on exit
+struct NamespaceLayer;
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for NamespaceLayer {
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        let name = match ctx.span(id) {
+            Some(span) if is_namespace(span.metadata()) => span.name(),
+            _ => return,
+        };
+        TRACKER.with(|tracker| {
+            if let Some(tracker) = tracker.borrow_mut().as_mut() {
+                let counts = tracker.attribute();
+                let path = match tracker.stack.last() {
+                    Some((parent, _)) => format!("{}/{}", parent, name),
+                    None => name.to_string(),
+                };
+                tracker.stack.push((path, counts));
+            }
+        });
+    }
+
+    fn on_exit(&self, id: &Id, ctx: Context<'_, S>) {
+        match ctx.span(id) {
+            Some(span) if is_namespace(span.metadata()) => {}
+            _ => return,
+        }
+        TRACKER.with(|tracker| {
+            if let Some(tracker) = tracker.borrow_mut().as_mut() {
+                let after = tracker.attribute();
+                if let Some((path, before)) = tracker.stack.pop() {
+                    let total = tracker.trace.namespaces.entry(path).or_default();
+                    total.constraints += after.constraints - before.constraints;
+                    total.witnesses += after.witnesses - before.witnesses;
+                    total.inputs += after.inputs - before.inputs;
+                }
+            }
+        });
+    }
+}
+
+// generates the constraints of the circuit into a fresh constraint system, recording namespaces,
+// and the full span path of each constraint for `ConstraintSystemRef::constraint_names`
+pub fn synthesize<C: ConstraintSynthesizer<Fr>>(circuit: C, mode: SynthesisMode) -> Result<(ConstraintSystemRef<Fr>, Trace), Error> {
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    cs.set_optimization_goal(OptimizationGoal::Constraints);
+    cs.set_mode(mode);
+
+    TRACKER.with(|tracker| {
+        let mut initial = Tracker { cs: cs.clone(), stack: vec![], trace: Trace::default() };
+        // the constant one
+        initial.attribute();
+        *tracker.borrow_mut() = Some(initial);
+    });
+    let subscriber = Registry::default().with(NamespaceLayer).with(ConstraintLayer::default());
+    let result = tracing::subscriber::with_default(subscriber, || circuit.generate_constraints(cs.clone()));
+    let mut tracker = TRACKER.with(|tracker| tracker.borrow_mut().take()).ok_or("namespace tracker missing")?;
+    result?;
+    tracker.attribute();
+
+    Ok((cs, tracker.trace))
+}
+
+// synthesizes the circuit in setup mode, so no witness is needed
+pub fn report<C: ConstraintSynthesizer<Fr>>(circuit: C) -> Result<Report, Error> {
+    let (cs, trace) = synthesize(circuit, SynthesisMode::Setup)?;
+
+    let count = |paths: &[String]| paths.iter().filter(|path| path.is_empty()).count();
+    let outside = Counts {
+        constraints: count(&trace.constraint_paths),
+        witnesses: count(&trace.witness_paths),
+        // not the constant one
+        inputs: count(&trace.input_paths) - 1,
+    };
+
+    cs.finalize();
+    let matrices = cs.to_matrices().ok_or("constraint system has no matrices")?;
+    Ok(Report {
+        constraints: matrices.num_constraints,
+        public_inputs: matrices.num_instance_variables - 1,
+        witnesses: matrices.num_witness_variables,
+        a_non_zero: matrices.a_num_non_zero,
+        b_non_zero: matrices.b_num_non_zero,
+        c_non_zero: matrices.c_num_non_zero,
+        namespaces: trace.namespaces,
+        outside,
+    })
+}
+
+// the top-level namespaces, which include their nested ones, and what is outside any of them
+#[cfg(test)]
+fn assert_rows_sum_to_totals(report: &Report) {
+    let mut sum = report.outside;
+    for (_, counts) in report.namespaces.iter().filter(|(path, _)| !path.contains('/')) {
+        sum.constraints += counts.constraints;
+        sum.witnesses += counts.witnesses;
+        sum.inputs += counts.inputs;
+    }
+    assert_eq!(sum, Counts { constraints: report.constraints, witnesses: report.witnesses, inputs: report.public_inputs });
+}
+
+#[test]
+fn test_cube_report() {
+    use crate::{to_fq, CubeDemo};
+
+    let report = report(CubeDemo { x: to_fq(3) }).unwrap();
+    println!("{}", report);
+
+    // x * x = x^2, x^2 * x = x^3, and (out - x^3 - x) * 1 = 0 from `enforce_equal`
+    assert_eq!(report.constraints, 3);
+    assert_eq!(report.public_inputs, 1);
+    assert_eq!(report.witnesses, 3);
+    assert_eq!((report.a_non_zero, report.b_non_zero, report.c_non_zero), (5, 3, 2));
+
+    // only the circuit's own labels, not the spans of the gadgets it calls
+    let paths: Vec<&str> = report.namespaces.keys().map(|path| path.as_str()).collect();
+    assert_eq!(paths, ["new witness x", "new witness x^2", "new witness x^3", "new witness x^3 + x"]);
+
+    let x_squared = report.namespaces["new witness x^2"];
+    assert_eq!(x_squared, Counts { constraints: 0, witnesses: 1, inputs: 0 });
+    assert_eq!(report.namespaces["new witness x^3 + x"].inputs, 1);
+    // the namespaces only allocate, the multiplications and `enforce_equal` are at the top level
+    assert_eq!(report.outside, Counts { constraints: 3, witnesses: 0, inputs: 0 });
+    assert!(report.to_string().contains("(outside any namespace)"));
+    assert_rows_sum_to_totals(&report);
+}
+
+#[test]
+fn test_hash_report() {
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use ark_crypto_primitives::crh::CRH;
+    use crate::hash::{HashDemo, TestCRH};
+
+    let params = TestCRH::setup(&mut StdRng::seed_from_u64(0u64)).unwrap();
+    let input = vec![1u8, 2, 3, 4];
+    let image = TestCRH::evaluate(&params, &input).unwrap();
+    let report = report(HashDemo { input: input.clone(), params, image }).unwrap();
+    println!("{}", report);
+
+    // every preimage bit is a public input constrained to be boolean
+    let preimage = report.namespaces["preimage"];
+    assert_eq!(preimage, Counts { constraints: 8 * input.len(), witnesses: 0, inputs: 8 * input.len() });
+    assert_eq!(report.public_inputs, 2 + 8 * input.len());
+    assert!(report.namespaces.keys().any(|path| path.starts_with("image_var")));
+    assert!(report.constraints > preimage.constraints);
+    assert_rows_sum_to_totals(&report);
+}
@@ -0,0 +1,205 @@
+use ark_bls12_381::Fr;
+use ark_ff::One;
+use ark_r1cs_std::prelude::*;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+
+// 0 marks an empty cell
+pub type Grid = [[u8; 9]; 9];
+
+// parse a 9x9 grid: digits 1-9 are filled cells, '0', '.' and '_' are empty cells,
+// whitespace and '|', '-', '+' separators are ignored
+pub fn parse_grid(text: &str) -> Result<Grid, String> {
+    let mut cells = vec![];
+    for c in text.chars() {
+        match c {
+            '1'..='9' => cells.push(c as u8 - b'0'),
+            '0' | '.' | '_' => cells.push(0),
+            '|' | '-' | '+' => {}
+            c if c.is_whitespace() => {}
+            c => return Err(format!("unexpected character {:?} in grid", c)),
+        }
+    }
+    if cells.len() != 81 {
+        return Err(format!("expected 81 cells, found {}", cells.len()));
+    }
+
+    let mut grid = [[0u8; 9]; 9];
+    for (i, cell) in cells.into_iter().enumerate() {
+        grid[i / 9][i % 9] = cell;
+    }
+    Ok(grid)
+}
+
+// the cells of each row, column and 3x3 box
+fn groups() -> Vec<Vec<(usize, usize)>> {
+    let mut groups = vec![];
+    for i in 0..9 {
+        groups.push((0..9).map(|j| (i, j)).collect());
+        groups.push((0..9).map(|j| (j, i)).collect());
+        groups.push((0..9).map(|j| (3 * (i / 3) + j / 3, 3 * (i % 3) + j % 3)).collect());
+    }
+    groups
+}
+
+// native reference: the solution is complete, agrees with the givens and every group is 1..9
+pub fn is_valid_solution(puzzle: &Grid, solution: &Grid) -> bool {
+    for i in 0..9 {
+        for j in 0..9 {
+            let (given, value) = (puzzle[i][j], solution[i][j]);
+            if !(1..=9).contains(&value) || (given != 0 && given != value) {
+                return false;
+            }
+        }
+    }
+    groups().iter().all(|group| {
+        let mut seen = [false; 10];
+        group.iter().all(|&(i, j)| !std::mem::replace(&mut seen[solution[i][j] as usize], true))
+    })
+}
+
+// proving that I know a valid solution of the public puzzle
+#[derive(Clone)]
+pub struct SudokuDemo {
+    pub puzzle: Grid,
+    pub solution: Grid,
+}
+
+impl ConstraintSynthesizer<Fr> for SudokuDemo {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+
+        // each filled cell is one-hot encoded: digits[i][j][d] is set iff the cell holds d + 1
+        let mut digits = vec![];
+        for i in 0..9 {
+            let mut row = vec![];
+            for j in 0..9 {
+                let given = FpVar::<Fr>::new_input(
+                    ark_relations::ns!(cs, "given"), || Ok(Fr::from(self.puzzle[i][j]))
+                )?;
+
+                let mut bits = vec![];
+                for d in 1..=9u8 {
+                    bits.push(Boolean::new_witness(
+                        ark_relations::ns!(cs, "cell digit"), || Ok(self.solution[i][j] == d)
+                    )?);
+                }
+
+                // exactly one digit per cell
+                let mut count = FpVar::<Fr>::zero();
+                let mut value = FpVar::<Fr>::zero();
+                for (d, bit) in bits.iter().enumerate() {
+                    let bit = FpVar::from(bit.clone());
+                    count += &bit;
+                    value += bit * Fr::from(d as u64 + 1);
+                }
+                count.enforce_equal(&FpVar::one())?;
+
+                // given * (value - given) == 0, so a given cell must keep its digit
+                given.mul_equals(&(value - &given), &FpVar::zero())?;
+
+                row.push(bits);
+            }
+            digits.push(row);
+        }
+
+        // every digit appears exactly once in every row, column and box
+        for group in groups() {
+            let mut counts = vec![FpVar::<Fr>::zero(); 9];
+            for &(i, j) in group.iter() {
+                for (count, bit) in counts.iter_mut().zip(digits[i][j].iter()) {
+                    *count += FpVar::from(bit.clone());
+                }
+            }
+            for count in counts.iter() {
+                count.enforce_equal(&FpVar::constant(Fr::one()))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// the verifier statement for a puzzle
+pub fn public_input(puzzle: &Grid) -> Vec<Fr> {
+    puzzle.iter().flatten().map(|&given| Fr::from(given)).collect()
+}
+
+#[cfg(test)]
+const PUZZLE: &str = "
+    53..7....
+    6..195...
+    .98....6.
+    8...6...3
+    4..8.3..1
+    7...2...6
+    .6....28.
+    ...419..5
+    ....8..79
+";
+
+#[cfg(test)]
+const SOLUTION: &str = "
+    534678912
+    672195348
+    198342567
+    859761423
+    426853791
+    713924856
+    961537284
+    287419635
+    345286179
+";
+
+#[test]
+fn test_sudoku_proof() {
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use ark_bls12_381::Bls12_381;
+    use ark_groth16::*;
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let puzzle = parse_grid(PUZZLE).unwrap();
+    let solution = parse_grid(SOLUTION).unwrap();
+    assert!(is_valid_solution(&puzzle, &solution));
+
+    let circuit = SudokuDemo { puzzle, solution };
+
+    let param = generate_random_parameters::<Bls12_381, _, _>(circuit.clone(), &mut rng).unwrap();
+    let proof = create_random_proof(circuit, &param, &mut rng).unwrap();
+    let pvk = prepare_verifying_key(&param.vk);
+
+    assert!(verify_proof(&pvk, &proof, &public_input(&puzzle)).unwrap());
+
+    // the proof does not verify against another puzzle
+    let mut other = puzzle;
+    other[0][2] = 1;
+    assert!(!verify_proof(&pvk, &proof, &public_input(&other)).unwrap());
+}
+
+#[test]
+fn test_sudoku_invalid_solution() {
+    use ark_relations::r1cs::ConstraintSystem;
+
+    let puzzle = parse_grid(PUZZLE).unwrap();
+    let solution = parse_grid(SOLUTION).unwrap();
+
+    // swapping two cells of a row keeps the row a permutation but breaks the columns
+    let mut swapped = solution;
+    swapped[0].swap(2, 3);
+    assert!(!is_valid_solution(&puzzle, &swapped));
+
+    // a solution that ignores a given
+    let mut ignored = solution;
+    ignored[0].swap(0, 1);
+    ignored[1].swap(0, 1);
+    assert!(!is_valid_solution(&puzzle, &ignored));
+
+    for bad in [swapped, ignored] {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        SudokuDemo { puzzle, solution: bad }.generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+
+    assert!(parse_grid("123").is_err());
+    assert!(parse_grid(&PUZZLE.replace('.', "x")).is_err());
+}
@@ -0,0 +1,162 @@
+use ark_bls12_381::Fr;
+use ark_crypto_primitives::commitment::pedersen::{
+    constraints::{ParametersVar, RandomnessVar},
+    Randomness,
+};
+use ark_crypto_primitives::commitment::CommitmentGadget;
+use ark_ed_on_bls12_381::{constraints::EdwardsVar, EdwardsAffine, EdwardsProjective as JubJub};
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_r1cs_std::uint8::UInt8;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, Namespace, SynthesisError};
+use ark_std::rand::Rng;
+
+use crate::commitment::{self, Parameters, ValueCommitmentGadget};
+
+// a hidden u64 value together with its public commitment
+#[derive(Clone)]
+pub struct Note {
+    pub value: u64,
+    pub randomness: Randomness<JubJub>,
+    pub commitment: EdwardsAffine,
+}
+
+impl Note {
+    pub fn new<R: Rng>(params: &Parameters, value: u64, rng: &mut R) -> Self {
+        let randomness = commitment::sample_randomness(rng);
+        let commitment = commitment::commit(params, value, &randomness).unwrap();
+        Note { value, randomness, commitment }
+    }
+}
+
+// native transfer: commits to the amount and the remaining balance,
+// returns None if the balance does not cover the amount
+pub fn transfer<R: Rng>(
+    params: &Parameters,
+    old_balance: &Note,
+    amount: u64,
+    rng: &mut R,
+) -> Option<TransferDemo> {
+    let new_value = old_balance.value.checked_sub(amount)?;
+    Some(TransferDemo {
+        params: params.clone(),
+        old_balance: old_balance.clone(),
+        amount: Note::new(params, amount, rng),
+        new_balance: Note::new(params, new_value, rng),
+    })
+}
+
+// proving that old_balance - amount = new_balance for the three public commitments
+#[derive(Clone)]
+pub struct TransferDemo {
+    pub params: Parameters,
+    pub old_balance: Note,
+    pub amount: Note,
+    pub new_balance: Note,
+}
+
+// allocates the public commitment and the hidden opening of a note,
+// and returns the value as a field element
+fn open_note(
+    cs: impl Into<Namespace<Fr>>,
+    params: &ParametersVar<JubJub, EdwardsVar>,
+    note: &Note,
+) -> Result<FpVar<Fr>, SynthesisError> {
+    let ns = cs.into();
+    let cs = ns.cs();
+
+    let commitment = <EdwardsVar as AllocVar<EdwardsAffine, _>>::new_input(
+        ark_relations::ns!(cs, "commitment"), || Ok(note.commitment)
+    )?;
+
+    let value_bytes = UInt8::new_witness_vec(ark_relations::ns!(cs, "value"), &note.value.to_le_bytes())?;
+    let randomness = RandomnessVar::new_witness(ark_relations::ns!(cs, "randomness"), || Ok(&note.randomness))?;
+
+    let result = ValueCommitmentGadget::commit(params, &value_bytes, &randomness)?;
+    result.enforce_equal(&commitment)?;
+
+    // the value is rebuilt from its 64 committed bits, which is the range check
+    Boolean::le_bits_to_fp_var(&value_bytes.to_bits_le()?)
+}
+
+impl ConstraintSynthesizer<Fr> for TransferDemo {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+
+        let params = ParametersVar::new_constant(ark_relations::ns!(cs, "parameters"), &self.params)?;
+
+        let old_balance = open_note(ark_relations::ns!(cs, "old balance"), &params, &self.old_balance)?;
+        let amount = open_note(ark_relations::ns!(cs, "amount"), &params, &self.amount)?;
+        let new_balance = open_note(ark_relations::ns!(cs, "new balance"), &params, &self.new_balance)?;
+
+        // all three are below 2^64, so the sum cannot wrap around the field
+        old_balance.enforce_equal(&(amount + new_balance))?;
+
+        Ok(())
+    }
+}
+
+// the verifier statement: old balance, amount and new balance commitments
+pub fn public_input(transfer: &TransferDemo) -> Vec<Fr> {
+    [&transfer.old_balance, &transfer.amount, &transfer.new_balance]
+        .iter()
+        .flat_map(|note| vec![note.commitment.x, note.commitment.y])
+        .collect()
+}
+
+#[test]
+fn test_transfer_proof() {
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use ark_bls12_381::Bls12_381;
+    use ark_groth16::*;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let params = commitment::setup(&mut rng).unwrap();
+    let old_balance = Note::new(&params, 100, &mut rng);
+    let circuit = transfer(&params, &old_balance, 30, &mut rng).unwrap();
+    assert_eq!(circuit.new_balance.value, 70);
+
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    circuit.clone().generate_constraints(cs.clone()).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+
+    let param = generate_random_parameters::<Bls12_381, _, _>(circuit.clone(), &mut rng).unwrap();
+    let proof = create_random_proof(circuit.clone(), &param, &mut rng).unwrap();
+    let pvk = prepare_verifying_key(&param.vk);
+
+    assert!(verify_proof(&pvk, &proof, &public_input(&circuit)).unwrap());
+
+    // spending the whole balance leaves a commitment to zero
+    let circuit = transfer(&params, &old_balance, 100, &mut rng).unwrap();
+    let proof = create_random_proof(circuit.clone(), &param, &mut rng).unwrap();
+    assert!(verify_proof(&pvk, &proof, &public_input(&circuit)).unwrap());
+}
+
+#[test]
+fn test_transfer_overdraft() {
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use ark_relations::r1cs::ConstraintSystem;
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let params = commitment::setup(&mut rng).unwrap();
+    let old_balance = Note::new(&params, 100, &mut rng);
+    assert!(transfer(&params, &old_balance, 101, &mut rng).is_none());
+
+    // a new balance that does not add up
+    let mut circuit = transfer(&params, &old_balance, 30, &mut rng).unwrap();
+    circuit.new_balance = Note::new(&params, 71, &mut rng);
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    circuit.generate_constraints(cs.clone()).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+
+    // an amount above the balance needs a new balance of -1 = p - 1,
+    // which has no 64-bit opening, and no u64 wraps around to it
+    let mut circuit = transfer(&params, &old_balance, 100, &mut rng).unwrap();
+    circuit.amount = Note::new(&params, 101, &mut rng);
+    circuit.new_balance = Note::new(&params, u64::MAX, &mut rng);
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    circuit.generate_constraints(cs.clone()).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+}
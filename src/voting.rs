@@ -0,0 +1,296 @@
+use std::collections::HashSet;
+
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_crypto_primitives::crh::{pedersen, CRHGadget, TwoToOneCRH, CRH};
+use ark_crypto_primitives::merkle_tree::{constraints::PathVar, Config, MerkleTree, Path};
+use ark_crypto_primitives::Error;
+use ark_ff::to_bytes;
+use ark_ed_on_bls12_381::EdwardsProjective as JubJub;
+use ark_groth16::{verify_proof, PreparedVerifyingKey, Proof};
+use ark_r1cs_std::prelude::*;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::uint8::UInt8;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_std::cmp::Ordering;
+use ark_std::rand::Rng;
+
+use crate::hash::{ConstraintF, HashGadget, TwoToOneHash};
+
+#[derive(Clone)]
+pub(crate) struct RegistryConfig;
+
+// the registry is a Merkle tree over voter public keys, hashed with the `hash.rs` Pedersen compressor
+impl Config for RegistryConfig {
+    type LeafHash = TwoToOneHash;
+    type TwoToOneHash = TwoToOneHash;
+}
+
+pub type Registry = MerkleTree<RegistryConfig>;
+pub type RegistryPath = Path<RegistryConfig>;
+type RegistryPathVar = PathVar<RegistryConfig, HashGadget, HashGadget, ConstraintF>;
+
+pub type SecretKey = [u8; 32];
+
+// independent generators for each use of the hash, so that a public key
+// can never collide with a nullifier
+#[derive(Clone)]
+pub struct VotingParameters {
+    pub key: pedersen::Parameters<JubJub>,
+    pub nullifier: pedersen::Parameters<JubJub>,
+    pub leaf: pedersen::Parameters<JubJub>,
+    pub two_to_one: pedersen::Parameters<JubJub>,
+}
+
+impl VotingParameters {
+    pub fn setup<R: Rng>(rng: &mut R) -> Result<Self, Error> {
+        Ok(VotingParameters {
+            key: <TwoToOneHash as CRH>::setup(rng)?,
+            nullifier: <TwoToOneHash as CRH>::setup(rng)?,
+            leaf: <TwoToOneHash as CRH>::setup(rng)?,
+            two_to_one: <TwoToOneHash as TwoToOneCRH>::setup(rng)?,
+        })
+    }
+}
+
+// pk = H_key(sk)
+pub fn public_key(params: &VotingParameters, sk: &SecretKey) -> Result<Fr, Error> {
+    <TwoToOneHash as CRH>::evaluate(&params.key, sk)
+}
+
+// nullifier = H_nullifier(sk || election_id), the same for every ballot of a voter in one election
+pub fn nullifier(params: &VotingParameters, sk: &SecretKey, election_id: Fr) -> Result<Fr, Error> {
+    let mut input = sk.to_vec();
+    input.extend(to_bytes![election_id]?);
+    <TwoToOneHash as CRH>::evaluate(&params.nullifier, &input)
+}
+
+pub fn registry(params: &VotingParameters, public_keys: &[Fr]) -> Result<Registry, Error> {
+    Registry::new(&params.leaf, &params.two_to_one, public_keys)
+}
+
+// proving that my key is in the registry, my vote is below num_options
+// and the nullifier belongs to my key and this election
+#[derive(Clone)]
+pub struct VoteDemo {
+    pub params: VotingParameters,
+    pub root: Fr,
+    pub election_id: Fr,
+    pub nullifier: Fr,
+    pub vote: u64,
+    pub num_options: u64,
+    pub sk: SecretKey,
+    pub path: RegistryPath,
+}
+
+impl ConstraintSynthesizer<Fr> for VoteDemo {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+
+        let root = FpVar::<Fr>::new_input(ark_relations::ns!(cs, "registry root"), || Ok(self.root))?;
+        let election_id = FpVar::<Fr>::new_input(ark_relations::ns!(cs, "election id"), || Ok(self.election_id))?;
+        let nullifier = FpVar::<Fr>::new_input(ark_relations::ns!(cs, "nullifier"), || Ok(self.nullifier))?;
+        let vote = FpVar::<Fr>::new_input(ark_relations::ns!(cs, "vote"), || Ok(Fr::from(self.vote)))?;
+        let num_options = FpVar::<Fr>::new_input(
+            ark_relations::ns!(cs, "number of options"), || Ok(Fr::from(self.num_options))
+        )?;
+
+        let key_params = pedersen::constraints::CRHParametersVar::new_constant(
+            ark_relations::ns!(cs, "key parameters"), &self.params.key
+        )?;
+        let nullifier_params = pedersen::constraints::CRHParametersVar::new_constant(
+            ark_relations::ns!(cs, "nullifier parameters"), &self.params.nullifier
+        )?;
+        let leaf_params = pedersen::constraints::CRHParametersVar::new_constant(
+            ark_relations::ns!(cs, "leaf parameters"), &self.params.leaf
+        )?;
+        let two_to_one_params = pedersen::constraints::CRHParametersVar::new_constant(
+            ark_relations::ns!(cs, "two to one parameters"), &self.params.two_to_one
+        )?;
+
+        let sk = UInt8::new_witness_vec(ark_relations::ns!(cs, "secret key"), &self.sk)?;
+        let path = RegistryPathVar::new_witness(ark_relations::ns!(cs, "registry path"), || Ok(&self.path))?;
+
+        // the key is registered
+        let pk = <HashGadget as CRHGadget<TwoToOneHash, ConstraintF>>::evaluate(&key_params, &sk)?;
+        path.verify_membership(&leaf_params, &two_to_one_params, &root, &pk)?
+            .enforce_equal(&Boolean::TRUE)?;
+
+        // the nullifier is derived from the same key
+        let mut input = sk.clone();
+        input.extend(election_id.to_bytes()?);
+        let result = <HashGadget as CRHGadget<TwoToOneHash, ConstraintF>>::evaluate(&nullifier_params, &input)?;
+        result.enforce_equal(&nullifier)?;
+
+        // 0 <= vote < num_options
+        vote.enforce_cmp(&num_options, Ordering::Less, false)?;
+
+        Ok(())
+    }
+}
+
+// the verifier statement, in allocation order
+pub fn public_input(root: Fr, election_id: Fr, nullifier: Fr, vote: u64, num_options: u64) -> Vec<Fr> {
+    vec![root, election_id, nullifier, Fr::from(vote), Fr::from(num_options)]
+}
+
+#[derive(Debug, PartialEq)]
+pub enum BallotError {
+    InvalidVote,
+    InvalidProof,
+    DuplicateNullifier,
+}
+
+pub struct Ballot {
+    pub nullifier: Fr,
+    pub vote: u64,
+    pub proof: Proof<Bls12_381>,
+}
+
+// counts verified ballots of one election, rejecting a second ballot from the same key
+pub struct Tally {
+    pub root: Fr,
+    pub election_id: Fr,
+    pub counts: Vec<u64>,
+    nullifiers: HashSet<Fr>,
+}
+
+impl Tally {
+    pub fn new(root: Fr, election_id: Fr, num_options: u64) -> Self {
+        Tally {
+            root,
+            election_id,
+            counts: vec![0; num_options as usize],
+            nullifiers: HashSet::new(),
+        }
+    }
+
+    pub fn cast(&mut self, pvk: &PreparedVerifyingKey<Bls12_381>, ballot: &Ballot) -> Result<(), BallotError> {
+        if ballot.vote >= self.counts.len() as u64 {
+            return Err(BallotError::InvalidVote);
+        }
+        if self.nullifiers.contains(&ballot.nullifier) {
+            return Err(BallotError::DuplicateNullifier);
+        }
+
+        let statement = public_input(
+            self.root, self.election_id, ballot.nullifier, ballot.vote, self.counts.len() as u64
+        );
+        if !verify_proof(pvk, &ballot.proof, &statement).map_err(|_| BallotError::InvalidProof)? {
+            return Err(BallotError::InvalidProof);
+        }
+
+        self.nullifiers.insert(ballot.nullifier);
+        self.counts[ballot.vote as usize] += 1;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_vote_proof_and_tally() {
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use ark_groth16::*;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let params = VotingParameters::setup(&mut rng).unwrap();
+    let secret_keys: Vec<SecretKey> = (0..4).map(|_| rng.gen()).collect();
+    let public_keys: Vec<Fr> = secret_keys.iter().map(|sk| public_key(&params, sk).unwrap()).collect();
+    let tree = registry(&params, &public_keys).unwrap();
+
+    let election_id = Fr::from(2022u64);
+    let num_options = 3;
+
+    let ballot_circuit = |index: usize, vote: u64| VoteDemo {
+        params: params.clone(),
+        root: tree.root(),
+        election_id,
+        nullifier: nullifier(&params, &secret_keys[index], election_id).unwrap(),
+        vote,
+        num_options,
+        sk: secret_keys[index],
+        path: tree.generate_proof(index).unwrap(),
+    };
+
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    ballot_circuit(1, 2).generate_constraints(cs.clone()).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+
+    let param = generate_random_parameters::<Bls12_381, _, _>(ballot_circuit(0, 0), &mut rng).unwrap();
+    let pvk = prepare_verifying_key(&param.vk);
+
+    let mut tally = Tally::new(tree.root(), election_id, num_options);
+    for (index, vote) in [(0, 1), (1, 2), (2, 1)] {
+        let circuit = ballot_circuit(index, vote);
+        let ballot = Ballot {
+            nullifier: circuit.nullifier,
+            vote,
+            proof: create_random_proof(circuit, &param, &mut rng).unwrap(),
+        };
+        tally.cast(&pvk, &ballot).unwrap();
+    }
+    assert_eq!(tally.counts, vec![0, 2, 1]);
+
+    // voter 0 votes again
+    let circuit = ballot_circuit(0, 2);
+    let ballot = Ballot {
+        nullifier: circuit.nullifier,
+        vote: 2,
+        proof: create_random_proof(circuit, &param, &mut rng).unwrap(),
+    };
+    assert_eq!(tally.cast(&pvk, &ballot), Err(BallotError::DuplicateNullifier));
+
+    // the proof is bound to the vote it was made for
+    let circuit = ballot_circuit(3, 0);
+    let ballot = Ballot {
+        nullifier: circuit.nullifier,
+        vote: 1,
+        proof: create_random_proof(circuit, &param, &mut rng).unwrap(),
+    };
+    assert_eq!(tally.cast(&pvk, &ballot), Err(BallotError::InvalidProof));
+    assert_eq!(tally.counts, vec![0, 2, 1]);
+}
+
+#[test]
+fn test_vote_out_of_range_and_unregistered() {
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use ark_relations::r1cs::ConstraintSystem;
+
+    let mut rng = StdRng::seed_from_u64(0u64);
+
+    let params = VotingParameters::setup(&mut rng).unwrap();
+    let secret_keys: Vec<SecretKey> = (0..4).map(|_| rng.gen()).collect();
+    let public_keys: Vec<Fr> = secret_keys.iter().map(|sk| public_key(&params, sk).unwrap()).collect();
+    let tree = registry(&params, &public_keys).unwrap();
+    let election_id = Fr::from(2022u64);
+
+    // vote == num_options
+    let circuit = VoteDemo {
+        params: params.clone(),
+        root: tree.root(),
+        election_id,
+        nullifier: nullifier(&params, &secret_keys[0], election_id).unwrap(),
+        vote: 3,
+        num_options: 3,
+        sk: secret_keys[0],
+        path: tree.generate_proof(0).unwrap(),
+    };
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    circuit.generate_constraints(cs.clone()).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+
+    // a key that is not in the registry, reusing someone else's path
+    let outsider: SecretKey = rng.gen();
+    let circuit = VoteDemo {
+        params: params.clone(),
+        root: tree.root(),
+        election_id,
+        nullifier: nullifier(&params, &outsider, election_id).unwrap(),
+        vote: 0,
+        num_options: 3,
+        sk: outsider,
+        path: tree.generate_proof(0).unwrap(),
+    };
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    circuit.generate_constraints(cs.clone()).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+}